@@ -1,69 +1,254 @@
 extern crate bindgen;
+extern crate gcc;
+extern crate pkg_config;
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Where to find the libxml2 headers
+struct Library {
+  include_paths: Vec<PathBuf>,
+}
 
 fn main() {
-  // Tell cargo to tell rustc to link the system xml2
-  // shared library.
-  println!("cargo:rustc-link-lib=xml2");
-
-  // The bindgen::Builder is the main entry point
-  // to bindgen, and lets you build up options for
-  // the resulting bindings.
-  let bindings = bindgen::Builder::default()
+  let library = find_library();
+
+  let mut builder = bindgen::Builder::default();
+  for path in &library.include_paths {
+    builder = builder.clang_arg(format!("-I{}", path.display()));
+  }
+  let bindings = builder
       // The input header we would like to generate
       // bindings for.
       .header("src/libxml2/wrapper.h")
-      //.whitelist_recursively(false)
-
-      /*
-      .whitelist_type("xmlDocPtr")
-      .whitelist_type("xmlDoc")
-      .whitelist_type("_xmlDoc")
-
-      .whitelist_type("xmlElementType")
-
-      .whitelist_type("_xmlNode")
-      */
-      .whitelist_function("xmlNewDoc")
-      .whitelist_function("xmlFreeDoc")
-      .whitelist_function("xmlDocGetRootElement")
-      .whitelist_function("xmlReadMemory")
-      .whitelist_function("xmlReadFile")
-      .whitelist_function("xmlResetLastError")
-      .whitelist_function("xmlSetStructuredErrorFunc")
-      .whitelist_function("xmlNodeSetName")
-      .whitelist_function("xmlDocDumpMemoryEnc")
-      .whitelist_function("xmlDocDumpFormatMemoryEnc")
-      .whitelist_function("xmlBufNodeDump")
-      .whitelist_function("xmlNodeDump")
-      .whitelist_function("xmlBufferContent")
-      .whitelist_function("xmlBufferCreate")
-      .whitelist_function("xmlBufferFree")
-      .whitelist_function("xmlResetError")
-      .whitelist_function("xmlDocCopyNode")
-      .whitelist_function("xmlAddChild")
-      .whitelist_function("xmlUnlinkNode")
-      .whitelist_function("xmlFreeNode")
-      .whitelist_function("xmlSaveFile")
-      .whitelist_function("htmlReadFile")
-      .whitelist_function("xmlGetLastChild")
-      .whitelist_function("xmlNewDocNode")
-      .whitelist_function("xmlDocSetRootElement")
-      .whitelist_function("xmlAddPrevSibling")
-      .whitelist_function("xmlGetProp")
-      .whitelist_function("xmlHasProp")
-      .whitelist_function("xmlSetProp")
-      .whitelist_function("xmlRemoveProp")
-      .whitelist_function("xmlNodeGetContent")
-      .whitelist_function("xmlNodeAddContentLen")
-
-      // Homebrew location of libxml2 headers.
-      .clang_arg("-I/usr/include/libxml2")
+      // Everything libxml2 declares except the deprecated SAX1 callbacks,
+      // the platform headers are only pulled in as far as needed.
+      .whitelist_function("(xml|html|xlink|UTF8To|isolat1To).*")
+      .whitelist_function("(input|name|node|value)(Push|Pop)")
+      // The per-thread globals behind the xmlStructuredError macros
+      .whitelist_function("__xmlStructuredError(Context)?")
+      .whitelist_type("_?(xml|html|xlink|xpath).*")
+      .whitelist_var("(XML|HTML|XPATH|XLINK|xml|html|LIBXML).*")
       // Finish the builder and generate the bindings.
       .generate()
       // Unwrap the Result and panic on failure.
       .expect("Unable to generate bindings");
 
+  // Write the bindings to $OUT_DIR, src/libxml2/mod.rs includes them from there.
+  let out_path = PathBuf::from(env::var("OUT_DIR").unwrap());
   bindings
-      .write_to_file("src/libxml2/mod.rs")
+      .write_to_file(out_path.join("bindings.rs"))
       .expect("Couldn't write bindings!");
 }
+
+/// Finds libxml2, in order of precedence through
+///
+/// * the bundled source with the `vendored` feature
+/// * `LIBXML2_INCLUDE_DIR` and `LIBXML2_LIB_DIR`
+/// * pkg-config
+/// * xml2-config
+/// * the default system location
+///
+/// `LIBXML2_STATIC` links the library statically.
+fn find_library() -> Library {
+  for var in &["LIBXML2_INCLUDE_DIR", "LIBXML2_LIB_DIR", "LIBXML2_STATIC"] {
+    println!("cargo:rerun-if-env-changed={}", var);
+  }
+  if env::var_os("CARGO_FEATURE_VENDORED").is_some() {
+    return build_vendored();
+  }
+  let statik = env::var_os("LIBXML2_STATIC").is_some_and(|value| value != "0");
+  let kind = if statik { "static" } else { "dylib" };
+
+  let include_dir = env::var_os("LIBXML2_INCLUDE_DIR").map(PathBuf::from);
+  let lib_dir = env::var_os("LIBXML2_LIB_DIR").map(PathBuf::from);
+  if let Some(lib_dir) = lib_dir {
+    println!("cargo:rustc-link-search=native={}", lib_dir.display());
+    println!("cargo:rustc-link-lib={}=xml2", kind);
+    return Library {
+      include_paths: include_dir.into_iter().collect(),
+    };
+  }
+
+  // pkg-config prints the link flags itself
+  if let Ok(library) = pkg_config::Config::new().statik(statik).probe("libxml-2.0") {
+    return Library {
+      include_paths: include_dir.into_iter().chain(library.include_paths).collect(),
+    };
+  }
+
+  if let Some(include_paths) = xml2_config(kind) {
+    return Library {
+      include_paths: include_dir.into_iter().chain(include_paths).collect(),
+    };
+  }
+
+  println!("cargo:rustc-link-lib={}=xml2", kind);
+  Library {
+    include_paths: vec![include_dir.unwrap_or_else(|| PathBuf::from("/usr/include/libxml2"))],
+  }
+}
+
+/// Links the library as reported by `xml2-config --libs` and returns the include paths from `--cflags`
+fn xml2_config(kind: &str) -> Option<Vec<PathBuf>> {
+  let run = |arg: &str| -> Option<String> {
+    let output = Command::new("xml2-config").arg(arg).output().ok()?;
+    if output.status.success() {
+      String::from_utf8(output.stdout).ok()
+    } else {
+      None
+    }
+  };
+  let cflags = run("--cflags")?;
+  let libs = run("--libs")?;
+
+  for flag in libs.split_whitespace() {
+    if let Some(dir) = flag.strip_prefix("-L") {
+      println!("cargo:rustc-link-search=native={}", dir);
+    } else if flag == "-lxml2" {
+      println!("cargo:rustc-link-lib={}=xml2", kind);
+    } else if let Some(lib) = flag.strip_prefix("-l") {
+      println!("cargo:rustc-link-lib={}", lib);
+    }
+  }
+  Some(cflags.split_whitespace()
+       .filter_map(|flag| flag.strip_prefix("-I"))
+       .map(PathBuf::from)
+       .collect())
+}
+
+// The library sources of libxml2 2.9, without the test programs and DOCBparser.c
+const VENDORED_SOURCES: &[&str] = &[
+  "SAX.c", "SAX2.c", "buf.c", "c14n.c", "catalog.c", "chvalid.c", "debugXML.c", "dict.c",
+  "encoding.c", "entities.c", "error.c", "globals.c", "hash.c", "HTMLparser.c", "HTMLtree.c",
+  "legacy.c", "list.c", "nanoftp.c", "nanohttp.c", "parser.c", "parserInternals.c",
+  "pattern.c", "relaxng.c", "schematron.c", "threads.c", "tree.c", "uri.c", "valid.c",
+  "xinclude.c", "xlink.c", "xmlIO.c", "xmlmemory.c", "xmlmodule.c", "xmlreader.c",
+  "xmlregexp.c", "xmlsave.c", "xmlschemas.c", "xmlschemastypes.c", "xmlstring.c",
+  "xmlunicode.c", "xmlwriter.c", "xpath.c", "xpointer.c",
+];
+
+/// Compiles the libxml2 source tree in `vendor/libxml2`, or in `LIBXML2_SRC_DIR`, and links it statically.
+///
+/// The configure options are chosen through cargo features:
+/// `vendored-http` enables the HTTP and FTP clients, `vendored-iconv` uses iconv for encodings
+/// other than UTF-8, UTF-16 and ISO-8859-x.
+fn build_vendored() -> Library {
+  println!("cargo:rerun-if-env-changed=LIBXML2_SRC_DIR");
+  // config.h below describes a Linux libc, other targets would need configure's checks
+  let target_os = env::var("CARGO_CFG_TARGET_OS").unwrap_or_default();
+  if target_os != "linux" {
+    panic!("The vendored feature only supports Linux targets, not {}. \
+            Link a libxml2 built for the target through pkg-config or LIBXML2_LIB_DIR instead", target_os);
+  }
+  let source_dir = env::var_os("LIBXML2_SRC_DIR")
+      .map(PathBuf::from)
+      .unwrap_or_else(|| Path::new(env!("CARGO_MANIFEST_DIR")).join("vendor/libxml2"));
+  if !source_dir.join("parser.c").exists() {
+    panic!("The vendored feature needs the libxml2 source in {}, \
+            unpack a libxml2 2.9 release there or point LIBXML2_SRC_DIR to one", source_dir.display());
+  }
+  let http = env::var_os("CARGO_FEATURE_VENDORED_HTTP").is_some();
+  let iconv = env::var_os("CARGO_FEATURE_VENDORED_ICONV").is_some();
+
+  // Stand-ins for the headers configure would generate
+  let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap());
+  let generated = out_dir.join("libxml2-include");
+  fs::create_dir_all(generated.join("libxml")).unwrap();
+  let version = read_version(&source_dir.join("configure.ac"));
+  let template = fs::read_to_string(source_dir.join("include/libxml/xmlversion.h.in"))
+      .expect("Couldn't read xmlversion.h.in");
+  fs::write(generated.join("libxml/xmlversion.h"), xmlversion_h(&template, &version, http, iconv)).unwrap();
+  fs::write(generated.join("config.h"), config_h(iconv)).unwrap();
+
+  let mut build = gcc::Build::new();
+  build.include(&generated)
+      .include(source_dir.join("include"))
+      .include(&source_dir)
+      .define("HAVE_CONFIG_H", None)
+      .define("LIBXML_STATIC", None)
+      .define("_REENTRANT", None)
+      .warnings(false);
+  for file in VENDORED_SOURCES {
+    build.file(source_dir.join(file));
+  }
+  build.compile("xml2");
+
+  let target = env::var("TARGET").unwrap();
+  if iconv && (target.contains("apple") || target.contains("freebsd")) {
+    println!("cargo:rustc-link-lib=iconv");
+  }
+  if !target.contains("windows") {
+    println!("cargo:rustc-link-lib=m");
+    println!("cargo:rustc-link-lib=pthread");
+  }
+  println!("cargo:rerun-if-changed={}", source_dir.display());
+
+  Library {
+    include_paths: vec![generated, source_dir.join("include")],
+  }
+}
+
+/// The major, minor and micro version from the `LIBXML_*_VERSION=` lines of configure.ac
+fn read_version(configure: &Path) -> (u32, u32, u32) {
+  let configure = fs::read_to_string(configure).expect("Couldn't read configure.ac");
+  let component = |name: &str| -> u32 {
+    configure.lines()
+        .filter_map(|line| line.trim().strip_prefix(name))
+        .filter_map(|value| value.trim().parse().ok())
+        .next()
+        .unwrap_or_else(|| panic!("No {} in configure.ac", name))
+  };
+  (component("LIBXML_MAJOR_VERSION="), component("LIBXML_MINOR_VERSION="), component("LIBXML_MICRO_VERSION="))
+}
+
+fn xmlversion_h(template: &str, version: &(u32, u32, u32), http: bool, iconv: bool) -> String {
+  let (major, minor, micro) = *version;
+  let disabled = ["WITH_TRIO", "WITH_THREAD_ALLOC", "WITH_DOCB", "WITH_ICU", "WITH_DEBUG",
+                  "WITH_MEM_DEBUG", "WITH_RUN_DEBUG", "WITH_MODULES", "WITH_ZLIB", "WITH_LZMA"];
+  let mut header = template
+      .replace("@VERSION@", &format!("{}.{}.{}", major, minor, micro))
+      .replace("@LIBXML_VERSION_NUMBER@", &format!("{}", major * 10000 + minor * 100 + micro))
+      .replace("@LIBXML_VERSION_EXTRA@", "")
+      .replace("@MODULE_EXTENSION@", ".so")
+      .replace("@WITH_FTP@", if http { "1" } else { "0" })
+      .replace("@WITH_HTTP@", if http { "1" } else { "0" })
+      .replace("@WITH_ICONV@", if iconv { "1" } else { "0" });
+  for option in &disabled {
+    header = header.replace(&format!("@{}@", option), "0");
+  }
+  // Everything else is a feature enabled by default in configure
+  let mut enabled = String::with_capacity(header.len());
+  let mut rest = header.as_str();
+  while let Some(start) = rest.find("@WITH_") {
+    enabled.push_str(&rest[..start]);
+    let end = rest[start + 1..].find('@').map(|end| start + end + 2).unwrap_or(rest.len());
+    enabled.push('1');
+    rest = &rest[end..];
+  }
+  enabled.push_str(rest);
+  enabled
+}
+
+/// The configuration of a Linux target, glibc and musl provide all of it
+fn config_h(iconv: bool) -> String {
+  let mut config = String::new();
+  for header in &["CTYPE_H", "DLFCN_H", "ERRNO_H", "FCNTL_H", "FLOAT_H", "INTTYPES_H", "LIMITS_H",
+                  "MATH_H", "NETDB_H", "NETINET_IN_H", "PTHREAD_H", "STDARG_H", "STDINT_H",
+                  "STDLIB_H", "STRING_H", "SYS_SELECT_H", "SYS_SOCKET_H", "SYS_STAT_H",
+                  "SYS_TIME_H", "SYS_TYPES_H", "TIME_H", "UNISTD_H", "ARPA_INET_H"] {
+    config.push_str(&format!("#define HAVE_{} 1\n", header));
+  }
+  for function in &["FPRINTF", "FTIME", "GETTIMEOFDAY", "ISASCII", "LOCALTIME", "MMAP", "MUNMAP",
+                    "PRINTF", "SNPRINTF", "SPRINTF", "SSCANF", "STAT", "STRFTIME", "TIME",
+                    "VFPRINTF", "VSNPRINTF", "VSPRINTF", "VA_COPY", "LIBPTHREAD"] {
+    config.push_str(&format!("#define HAVE_{} 1\n", function));
+  }
+  if iconv {
+    config.push_str("#define HAVE_ICONV_H 1\n#define ICONV_CONST\n");
+  }
+  config.push_str("#define XML_SOCKLEN_T socklen_t\n#define SEND_ARG2_CAST\n#define GETHOSTBYNAME_ARG_CAST\n");
+  config
+}
@@ -0,0 +1,90 @@
+//! XML Catalogs, resolving public and system identifiers of DTDs and entities to local files.
+//!
+//! The functions here change the global catalog used by every parse, a `Parser` can additionally
+//! consult catalogs of its own, see `Parser::add_catalog`.
+//! Resources are resolved through catalogs whenever libxml2 loads them itself, that is unless a
+//! `Parser` has an entity loader or a security policy.
+
+use std::ffi::CString;
+use std::ptr;
+
+use libc;
+
+use libxml2::{xmlChar,
+              xmlLoadCatalog,
+              xmlCatalogAdd,
+              xmlInitializeCatalog,
+              xmlCatalogResolve};
+
+use tree::{init_parser, xml_bytes};
+
+/// Adds the catalog file `filename` to the global catalog
+pub fn load_catalog(filename: &str) -> Result<(), ()> {
+    init_parser();
+    let c_filename = CString::new(filename).map_err(|_| ())?;
+    match unsafe { xmlLoadCatalog(c_filename.as_ptr()) } {
+        0 => Ok(()),
+        _ => Err(()),
+    }
+}
+
+/// Adds an entry to the global catalog, `kind` is the name of the catalog element,
+/// e.g. `"public"`, `"system"`, `"rewriteSystem"` or `"uri"`.
+///
+/// ```ignore
+/// catalog::add("public", "-//OASIS//DTD DocBook XML V4.5//EN", "file:///usr/share/xml/docbook/docbookx.dtd")?;
+/// ```
+pub fn add(kind: &str, orig: &str, replace: &str) -> Result<(), ()> {
+    init_parser();
+    let c_kind = CString::new(kind).map_err(|_| ())?;
+    let c_orig = CString::new(orig).map_err(|_| ())?;
+    let c_replace = CString::new(replace).map_err(|_| ())?;
+    let result = unsafe {
+        // Only a "catalog" entry creates the default catalog, other kinds are added to it
+        xmlInitializeCatalog();
+        xmlCatalogAdd(c_kind.as_ptr() as *const xmlChar, c_orig.as_ptr() as *const xmlChar, c_replace.as_ptr() as *const xmlChar)
+    };
+    match result {
+        0 => Ok(()),
+        _ => Err(()),
+    }
+}
+
+/// Looks up the URI the global catalog maps a public and/or system identifier to
+pub fn resolve(public_id: Option<&str>, system_id: Option<&str>) -> Option<String> {
+    init_parser();
+    let c_public_id = match public_id.map(CString::new) {
+        Some(Ok(id)) => Some(id),
+        Some(Err(_)) => return None,
+        None => None,
+    };
+    let c_system_id = match system_id.map(CString::new) {
+        Some(Ok(id)) => Some(id),
+        Some(Err(_)) => return None,
+        None => None,
+    };
+    let as_ptr = |id: &Option<CString>| id.as_ref().map_or(ptr::null(), |id| id.as_ptr() as *const xmlChar);
+    unsafe {
+        let uri_ptr = xmlCatalogResolve(as_ptr(&c_public_id), as_ptr(&c_system_id));
+        if uri_ptr.is_null() {
+            return None;
+        }
+        let uri = String::from_utf8_lossy(&xml_bytes(uri_ptr)).into_owned();
+        libc::free(uri_ptr as *mut libc::c_void);
+        Some(uri)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn global_catalog_test() {
+        assert_eq!(resolve(Some("-//Example//DTD Global//EN"), None), None);
+        add("public", "-//Example//DTD Global//EN", "file:///opt/example/global.dtd").unwrap();
+        assert_eq!(resolve(Some("-//Example//DTD Global//EN"), None), Some(String::from("file:///opt/example/global.dtd")));
+        load_catalog("tests/resources/global-catalog.xml").unwrap();
+        assert_eq!(resolve(None, Some("http://example.com/loaded.dtd")), Some(String::from("file:///opt/example/loaded.dtd")));
+    }
+}
@@ -0,0 +1,506 @@
+//! Compilation of CSS selectors to XPath 1.0 expressions
+//!
+//! Supported are type and universal selectors, `#id`, `.class`, the attribute selectors
+//! `[a]`, `[a=v]`, `[a~=v]`, `[a|=v]`, `[a^=v]`, `[a$=v]` and `[a*=v]`, the combinators
+//! ` `, `>`, `+` and `~`, selector groups separated by `,` and the structural pseudo-classes
+//! `:root`, `:empty`, `:first-child`, `:last-child`, `:only-child`, `:nth-child()`,
+//! `:nth-last-child()`, the `*-of-type` variants and `:not()`.
+
+use std::iter::Peekable;
+use std::str::Chars;
+
+use error::Error;
+use xpath::xpath_literal;
+
+/// Compiles a CSS selector to an XPath expression selecting the matching elements of a document
+pub fn to_xpath(selector: &str) -> Result<String, Error> {
+    compile(selector, "//")
+}
+
+/// Compiles a CSS selector to a relative XPath expression, which selects the matching descendants
+/// of the context node. The whole selector is matched below the context node, like `:scope` in CSS.
+pub fn to_relative_xpath(selector: &str) -> Result<String, Error> {
+    compile(selector, ".//")
+}
+
+// `descendants` selects the descendants the first compound selector is matched against
+fn compile(selector: &str, descendants: &'static str) -> Result<String, Error> {
+    let mut parser = SelectorParser { chars: selector.chars().peekable(), position: 0, descendants };
+    let mut alternatives = Vec::new();
+    loop {
+        alternatives.push(parser.parse_complex()?);
+        parser.skip_whitespace();
+        match parser.next() {
+            Some(',') => continue,
+            None => break,
+            Some(c) => return Err(parser.error(&format!("unexpected '{}'", c))),
+        }
+    }
+    Ok(alternatives.join(" | "))
+}
+
+struct SelectorParser<'a> {
+    chars: Peekable<Chars<'a>>,
+    position: usize,
+    descendants: &'static str,
+}
+
+/// A compound selector, such as `div.item[href]`
+struct Compound {
+    name: Option<String>,
+    conditions: Vec<String>,
+}
+
+impl Compound {
+    fn name_test(&self) -> &str {
+        match self.name {
+            Some(ref name) => name,
+            None => "*",
+        }
+    }
+
+    fn to_step(&self) -> String {
+        let mut step = String::from(self.name_test());
+        for condition in &self.conditions {
+            step.push_str(&format!("[{}]", condition));
+        }
+        step
+    }
+
+    fn to_condition(&self) -> String {
+        let mut conditions = Vec::new();
+        if let Some(ref name) = self.name {
+            conditions.push(format!("self::{}", name));
+        }
+        conditions.extend(self.conditions.iter().map(|c| format!("({})", c)));
+        if conditions.is_empty() {
+            String::from("true()")
+        } else {
+            conditions.join(" and ")
+        }
+    }
+}
+
+impl<'a> SelectorParser<'a> {
+    fn peek(&mut self) -> Option<char> {
+        self.chars.peek().cloned()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.chars.next();
+        if c.is_some() {
+            self.position += 1;
+        }
+        c
+    }
+
+    fn error(&self, message: &str) -> Error {
+        Error::InvalidSelector(format!("{} at position {}", message, self.position))
+    }
+
+    fn skip_whitespace(&mut self) -> bool {
+        let mut skipped = false;
+        while let Some(c) = self.peek() {
+            if !c.is_whitespace() {
+                break;
+            }
+            self.next();
+            skipped = true;
+        }
+        skipped
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), Error> {
+        match self.next() {
+            Some(c) if c == expected => Ok(()),
+            Some(c) => Err(self.error(&format!("expected '{}' but found '{}'", expected, c))),
+            None => Err(self.error(&format!("expected '{}'", expected))),
+        }
+    }
+
+    fn parse_complex(&mut self) -> Result<String, Error> {
+        self.skip_whitespace();
+        let first = self.parse_compound()?;
+        let mut xpath = format!("{}{}", self.descendants, first.to_step());
+        loop {
+            let whitespace = self.skip_whitespace();
+            let combinator = match self.peek() {
+                Some(c) if c == '>' || c == '+' || c == '~' => {
+                    self.next();
+                    self.skip_whitespace();
+                    c
+                },
+                Some(c) if whitespace && c != ',' => ' ',
+                _ => return Ok(xpath),
+            };
+            let compound = self.parse_compound()?;
+            match combinator {
+                '>' => xpath.push_str(&format!("/{}", compound.to_step())),
+                '+' => xpath.push_str(&format!("/following-sibling::*[1]/self::{}", compound.to_step())),
+                '~' => xpath.push_str(&format!("/following-sibling::{}", compound.to_step())),
+                _ => xpath.push_str(&format!("//{}", compound.to_step())),
+            }
+        }
+    }
+
+    fn parse_compound(&mut self) -> Result<Compound, Error> {
+        let mut universal = false;
+        let name = match self.peek() {
+            Some('*') => {
+                self.next();
+                universal = true;
+                None
+            },
+            Some(c) if is_name_char(c) || c == '\\' => Some(self.parse_qname()?),
+            _ => None,
+        };
+        let mut compound = Compound { name, conditions: Vec::new() };
+        loop {
+            match self.peek() {
+                Some('#') => {
+                    self.next();
+                    let id = self.parse_identifier()?;
+                    compound.conditions.push(format!("@id = {}", xpath_literal(&id)));
+                },
+                Some('.') => {
+                    self.next();
+                    let class = self.parse_identifier()?;
+                    compound.conditions.push(contains_word("@class", &class));
+                },
+                Some('[') => {
+                    self.next();
+                    let condition = self.parse_attribute()?;
+                    compound.conditions.push(condition);
+                },
+                Some(':') => {
+                    self.next();
+                    let condition = self.parse_pseudo_class(&compound)?;
+                    compound.conditions.push(condition);
+                },
+                _ => break,
+            }
+        }
+        if compound.name.is_none() && compound.conditions.is_empty() && !universal {
+            return Err(match self.peek() {
+                Some(c) => self.error(&format!("unexpected '{}'", c)),
+                None => self.error("expected a selector"),
+            });
+        }
+        Ok(compound)
+    }
+
+    // Decodes the escape following a backslash as in CSS Syntax 4.3.7, `None` for an escaped newline
+    fn parse_escape(&mut self) -> Option<char> {
+        match self.peek() {
+            Some(c) if c.is_ascii_hexdigit() => {
+                let mut code = 0;
+                for _ in 0..6 {
+                    match self.peek().and_then(|c| c.to_digit(16)) {
+                        Some(digit) => {
+                            self.next();
+                            code = code * 16 + digit;
+                        },
+                        None => break,
+                    }
+                }
+                // A single whitespace ends the hex digits
+                if self.peek().is_some_and(|c| c == ' ' || c == '\t' || c == '\n' || c == '\r' || c == '\x0c') {
+                    self.next();
+                }
+                // Zero, surrogates and values above U+10FFFF are replaced
+                Some(if code == 0 { '\u{fffd}' } else { char::from_u32(code).unwrap_or('\u{fffd}') })
+            },
+            Some('\n') | Some('\r') | Some('\x0c') => None,
+            Some(_) => self.next(),
+            None => Some('\u{fffd}'),
+        }
+    }
+
+    fn parse_identifier(&mut self) -> Result<String, Error> {
+        let mut identifier = String::new();
+        while let Some(c) = self.peek() {
+            if c == '\\' {
+                self.next();
+                match self.parse_escape() {
+                    Some(escaped) => identifier.push(escaped),
+                    None => return Err(self.error("invalid escape")),
+                }
+            } else if is_name_char(c) {
+                self.next();
+                identifier.push(c);
+            } else {
+                break;
+            }
+        }
+        if identifier.is_empty() {
+            Err(self.error("expected an identifier"))
+        } else {
+            Ok(identifier)
+        }
+    }
+
+    // An element or attribute name, which is written into the expression as a name test
+    fn parse_qname(&mut self) -> Result<String, Error> {
+        let name = self.parse_identifier()?;
+        if is_qname(&name) {
+            Ok(name)
+        } else {
+            Err(self.error(&format!("'{}' is not a valid element or attribute name", name)))
+        }
+    }
+
+    fn parse_string(&mut self, quote: char) -> Result<String, Error> {
+        let mut value = String::new();
+        loop {
+            match self.next() {
+                Some('\\') => match self.peek() {
+                    None => {},
+                    // An escaped newline continues the string on the next line
+                    Some('\n') | Some('\x0c') => {
+                        self.next();
+                    },
+                    Some('\r') => {
+                        self.next();
+                        if self.peek() == Some('\n') {
+                            self.next();
+                        }
+                    },
+                    Some(_) => value.extend(self.parse_escape()),
+                },
+                Some(c) if c == quote => return Ok(value),
+                Some(c) => value.push(c),
+                None => return Err(self.error("unterminated string")),
+            }
+        }
+    }
+
+    fn parse_attribute(&mut self) -> Result<String, Error> {
+        self.skip_whitespace();
+        let name = self.parse_qname()?;
+        let attribute = format!("@{}", name);
+        self.skip_whitespace();
+        let operator = match self.next() {
+            Some(']') => return Ok(attribute),
+            Some('=') => '=',
+            Some(c) if "~|^$*".contains(c) => {
+                self.expect('=')?;
+                c
+            },
+            Some(c) => return Err(self.error(&format!("unexpected '{}' in attribute selector", c))),
+            None => return Err(self.error("unterminated attribute selector")),
+        };
+        self.skip_whitespace();
+        let value = match self.peek() {
+            Some(quote) if quote == '"' || quote == '\'' => {
+                self.next();
+                self.parse_string(quote)?
+            },
+            _ => self.parse_identifier()?,
+        };
+        self.skip_whitespace();
+        self.expect(']')?;
+
+        let literal = xpath_literal(&value);
+        // Substring matches of an empty value never match
+        if value.is_empty() && operator != '=' && operator != '|' {
+            return Ok(String::from("false()"));
+        }
+        Ok(match operator {
+            '=' => format!("{} = {}", attribute, literal),
+            '~' => contains_word(&attribute, &value),
+            '|' => format!("{} = {} or starts-with({}, {})", attribute, literal, attribute, xpath_literal(&format!("{}-", value))),
+            '^' => format!("starts-with({}, {})", attribute, literal),
+            '$' => format!("substring({}, string-length({}) - {}) = {}", attribute, attribute, value.chars().count() - 1, literal),
+            _ => format!("contains({}, {})", attribute, literal),
+        })
+    }
+
+    fn parse_pseudo_class(&mut self, compound: &Compound) -> Result<String, Error> {
+        if self.peek() == Some(':') {
+            return Err(self.error("pseudo-elements are not supported"));
+        }
+        let name = self.parse_identifier()?.to_lowercase();
+        let argument = if self.peek() == Some('(') {
+            self.next();
+            Some(self.parse_argument()?)
+        } else {
+            None
+        };
+        let sibling_test = if name.ends_with("-of-type") {
+            match compound.name {
+                Some(ref name) => name.clone(),
+                None => return Err(self.error(&format!(":{} requires a type selector", name))),
+            }
+        } else {
+            String::from("*")
+        };
+        let preceding = format!("count(preceding-sibling::{}) + 1", sibling_test);
+        let following = format!("count(following-sibling::{}) + 1", sibling_test);
+
+        let condition = match (name.as_str(), argument) {
+            ("root", None) => String::from("not(parent::*)"),
+            ("empty", None) => String::from("not(*) and not(text())"),
+            ("first-child", None) | ("first-of-type", None) => format!("not(preceding-sibling::{})", sibling_test),
+            ("last-child", None) | ("last-of-type", None) => format!("not(following-sibling::{})", sibling_test),
+            ("only-child", None) | ("only-of-type", None) => {
+                format!("not(preceding-sibling::{}) and not(following-sibling::{})", sibling_test, sibling_test)
+            },
+            ("nth-child", Some(argument)) | ("nth-of-type", Some(argument)) => {
+                let (a, b) = self.parse_nth(&argument)?;
+                nth_condition(&preceding, a, b)
+            },
+            ("nth-last-child", Some(argument)) | ("nth-last-of-type", Some(argument)) => {
+                let (a, b) = self.parse_nth(&argument)?;
+                nth_condition(&following, a, b)
+            },
+            ("not", Some(argument)) => {
+                let mut inner = SelectorParser { chars: argument.chars().peekable(), position: 0, descendants: self.descendants };
+                inner.skip_whitespace();
+                let negated = inner.parse_compound()?;
+                inner.skip_whitespace();
+                if inner.peek().is_some() {
+                    return Err(self.error(":not() takes a single compound selector"));
+                }
+                format!("not({})", negated.to_condition())
+            },
+            _ => return Err(self.error(&format!("unsupported pseudo-class :{}", name))),
+        };
+        Ok(condition)
+    }
+
+    // The text between the parentheses of a functional pseudo-class
+    fn parse_argument(&mut self) -> Result<String, Error> {
+        let mut argument = String::new();
+        let mut depth = 0;
+        loop {
+            match self.next() {
+                Some(')') if depth == 0 => return Ok(argument),
+                Some(c) => {
+                    if c == '(' {
+                        depth += 1;
+                    } else if c == ')' {
+                        depth -= 1;
+                    }
+                    argument.push(c);
+                },
+                None => return Err(self.error("unterminated argument")),
+            }
+        }
+    }
+
+    // Parses the `an+b` notation, as well as `odd` and `even`
+    fn parse_nth(&self, argument: &str) -> Result<(i64, i64), Error> {
+        let argument: String = argument.chars().filter(|c| !c.is_whitespace()).collect::<String>().to_lowercase();
+        let invalid = || self.error(&format!("invalid argument '{}'", argument));
+        match argument.as_str() {
+            "odd" => return Ok((2, 1)),
+            "even" => return Ok((2, 0)),
+            _ => {},
+        }
+        match argument.find('n') {
+            None => argument.parse().map(|b| (0, b)).map_err(|_| invalid()),
+            Some(index) => {
+                let a = match &argument[..index] {
+                    "" | "+" => 1,
+                    "-" => -1,
+                    a => a.parse().map_err(|_| invalid())?,
+                };
+                let b = match &argument[index + 1..] {
+                    "" => 0,
+                    b if b.starts_with('+') => b[1..].parse().map_err(|_| invalid())?,
+                    b if b.starts_with('-') => b.parse().map_err(|_| invalid())?,
+                    _ => return Err(invalid()),
+                };
+                Ok((a, b))
+            },
+        }
+    }
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '-' || c == '_' || !c.is_ascii()
+}
+
+// A name as in the Namespaces in XML recommendation, an NCName optionally preceded by a prefix and ':'
+fn is_qname(name: &str) -> bool {
+    let mut parts = name.splitn(2, ':');
+    parts.all(|part| {
+        let mut chars = part.chars();
+        chars.next().is_some_and(is_name_start_char) && chars.all(|c| is_name_start_char(c) || is_xml_name_char(c))
+    })
+}
+
+// NameStartChar of XML 1.0 without ':'
+fn is_name_start_char(c: char) -> bool {
+    matches!(c,
+        'A'..='Z' | 'a'..='z' | '_' | '\u{c0}'..='\u{d6}' | '\u{d8}'..='\u{f6}' | '\u{f8}'..='\u{2ff}' |
+        '\u{370}'..='\u{37d}' | '\u{37f}'..='\u{1fff}' | '\u{200c}'..='\u{200d}' | '\u{2070}'..='\u{218f}' |
+        '\u{2c00}'..='\u{2fef}' | '\u{3001}'..='\u{d7ff}' | '\u{f900}'..='\u{fdcf}' | '\u{fdf0}'..='\u{fffd}' |
+        '\u{10000}'..='\u{effff}')
+}
+
+// The characters NameChar of XML 1.0 adds to NameStartChar
+fn is_xml_name_char(c: char) -> bool {
+    matches!(c, '-' | '.' | '0'..='9' | '\u{b7}' | '\u{300}'..='\u{36f}' | '\u{203f}'..='\u{2040}')
+}
+
+// The condition for an `an+b` position, where `position` is the 1-based position of the element
+fn nth_condition(position: &str, a: i64, b: i64) -> String {
+    if a == 0 {
+        format!("{} = {}", position, b)
+    } else {
+        format!("({} - {}) mod {} = 0 and ({} - {}) div {} >= 0", position, b, a, position, b, a)
+    }
+}
+
+// Matches `word` as one of the whitespace separated words of `attribute`
+fn contains_word(attribute: &str, word: &str) -> String {
+    if word.is_empty() || word.contains(char::is_whitespace) {
+        return String::from("false()");
+    }
+    format!("contains(concat(' ', normalize-space({}), ' '), {})", attribute, xpath_literal(&format!(" {} ", word)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn to_xpath_test() {
+        assert_eq!(to_xpath("div").unwrap(), "//div");
+        assert_eq!(to_xpath("*").unwrap(), "//*");
+        assert_eq!(to_xpath("#main").unwrap(), "//*[@id = 'main']");
+        assert_eq!(to_xpath("div.item > a[href^='http']").unwrap(),
+                   "//div[contains(concat(' ', normalize-space(@class), ' '), ' item ')]/a[starts-with(@href, 'http')]");
+        assert_eq!(to_xpath("h1 + p, ul ~ p").unwrap(),
+                   "//h1/following-sibling::*[1]/self::p | //ul/following-sibling::p");
+        assert_eq!(to_xpath("li:nth-child(2n+1)").unwrap(),
+                   "//li[(count(preceding-sibling::*) + 1 - 1) mod 2 = 0 and (count(preceding-sibling::*) + 1 - 1) div 2 >= 0]");
+        assert_eq!(to_xpath("p:not(.a)").unwrap(),
+                   "//p[not((contains(concat(' ', normalize-space(@class), ' '), ' a ')))]");
+        assert_eq!(to_xpath("[title=\"it's\"]").unwrap(), "//*[@title = \"it's\"]");
+        assert_eq!(to_relative_xpath("ul > li, p").unwrap(), ".//ul/li | .//p");
+    }
+
+    #[test]
+    fn escape_test() {
+        assert_eq!(to_xpath(".a\\:b").unwrap(), "//*[contains(concat(' ', normalize-space(@class), ' '), ' a:b ')]");
+        assert_eq!(to_xpath("#x\\]").unwrap(), "//*[@id = 'x]']");
+        assert_eq!(to_xpath("#\\31 23").unwrap(), "//*[@id = '123']");
+        assert_eq!(to_xpath("#\\27x").unwrap(), "//*[@id = \"'x\"]");
+        assert_eq!(to_xpath("\\64iv").unwrap(), "//div");
+        assert_eq!(to_xpath("[title='a\\\nb']").unwrap(), "//*[@title = 'ab']");
+        assert_eq!(to_xpath("#\\0").unwrap(), "//*[@id = '\u{fffd}']");
+    }
+
+    #[test]
+    fn invalid_selector_test() {
+        for selector in &["", "div >", "a[href", "p::before", "p:hover", "*:first-of-type", "li:nth-child(x)", "a,", "div $",
+                          "a\\]b", "\\31 div", "[a\\ b]", "p\\\n"] {
+            match to_xpath(selector) {
+                Err(Error::InvalidSelector(_)) => {},
+                result => panic!("{:?} compiled to {:?}", selector, result),
+            }
+        }
+    }
+}
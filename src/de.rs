@@ -0,0 +1,458 @@
+//! Deserialization of Rust values from a `Node` with serde
+//!
+//! The attributes of an element map to fields named `@name`, its child elements to fields
+//! named after the elements and its own text to the field `$value`. Child elements occurring
+//! more than once deserialize into sequences, elements without attributes and child elements
+//! deserialize into plain values parsed from their text.
+
+use std::fmt::Display;
+use std::str::FromStr;
+use std::vec;
+
+use serde::de::{self, Visitor, DeserializeOwned, DeserializeSeed, IntoDeserializer,
+                MapAccess, SeqAccess, EnumAccess, VariantAccess, Unexpected};
+
+use error::Error;
+use tree::document::Document;
+use tree::node::Node;
+
+/// Deserializes a `T` from the element `node`
+pub fn from_node<T: DeserializeOwned>(node: &Node) -> Result<T, Error> {
+    T::deserialize(Deserializer::new(node.clone()))
+}
+
+/// Deserializes a `T` from the root element of `document`
+pub fn from_document<T: DeserializeOwned>(document: &Document) -> Result<T, Error> {
+    match document.get_root_element() {
+        Some(root) => from_node(&root),
+        None => Err(Error::Serde(String::from("the document has no root element"))),
+    }
+}
+
+/// A serde `Deserializer` reading from an element
+pub struct Deserializer {
+    node: Node,
+}
+
+impl Deserializer {
+    /// Creates a deserializer reading from the element `node`
+    pub fn new(node: Node) -> Deserializer {
+        Deserializer { node }
+    }
+
+    fn value(&self) -> ValueDeserializer {
+        ValueDeserializer(self.node.get_text_content())
+    }
+
+    fn is_leaf(&self) -> bool {
+        self.node.get_properties().is_empty() && self.node.get_child_elements().is_empty()
+    }
+}
+
+macro_rules! forward_to_value {
+    ($($method:ident)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+                self.value().$method(visitor)
+            }
+        )*
+    };
+}
+
+impl<'de> de::Deserializer<'de> for Deserializer {
+    type Error = Error;
+
+    forward_to_value! {
+        deserialize_bool deserialize_i8 deserialize_i16 deserialize_i32 deserialize_i64
+        deserialize_u8 deserialize_u16 deserialize_u32 deserialize_u64 deserialize_f32 deserialize_f64
+        deserialize_char deserialize_str deserialize_string deserialize_bytes deserialize_byte_buf
+        deserialize_identifier
+    }
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        if self.is_leaf() {
+            self.value().deserialize_any(visitor)
+        } else {
+            self.deserialize_map(visitor)
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_seq(ElementSeq(self.node.get_child_elements().into_iter()))
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(self, _name: &'static str, _len: usize, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_map(ElementMap::new(&self.node))
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(self, _name: &'static str, _fields: &'static [&'static str], visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_map(visitor)
+    }
+
+    /// The first child element selects the variant, an element without child elements is a unit variant named by its text
+    fn deserialize_enum<V: Visitor<'de>>(self, name: &'static str, variants: &'static [&'static str], visitor: V) -> Result<V::Value, Error> {
+        match self.node.get_child_elements().into_iter().next() {
+            Some(variant) => visitor.visit_enum(ElementEnum(variant)),
+            None => self.value().deserialize_enum(name, variants, visitor),
+        }
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_unit()
+    }
+}
+
+/// The value of an attribute or the text of an element
+struct ValueDeserializer(String);
+
+impl ValueDeserializer {
+    fn parse<T: FromStr>(&self) -> Result<T, Error> where T::Err: Display {
+        self.0.trim().parse().map_err(|err| Error::Serde(format!("invalid value '{}': {}", self.0, err)))
+    }
+}
+
+macro_rules! deserialize_parsed {
+    ($($method:ident => $visit:ident,)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+                visitor.$visit(self.parse()?)
+            }
+        )*
+    };
+}
+
+impl<'de> de::Deserializer<'de> for ValueDeserializer {
+    type Error = Error;
+
+    deserialize_parsed! {
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+        deserialize_f32 => visit_f32,
+        deserialize_f64 => visit_f64,
+        deserialize_char => visit_char,
+    }
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_string(self.0)
+    }
+
+    /// Follows the rules of `xs:boolean`
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.0.trim() {
+            "true" | "1" => visitor.visit_bool(true),
+            "false" | "0" => visitor.visit_bool(false),
+            _ => Err(de::Error::invalid_value(Unexpected::Str(&self.0), &visitor)),
+        }
+    }
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_string(self.0)
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_string(self.0)
+    }
+
+    fn deserialize_bytes<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_byte_buf(self.0.into_bytes())
+    }
+
+    fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_byte_buf(self.0.into_bytes())
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    /// A whitespace separated list, like `xs:list`
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let items: Vec<String> = self.0.split_whitespace().map(|item| item.to_owned()).collect();
+        visitor.visit_seq(ValueSeq(items.into_iter()))
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(self, _name: &'static str, _len: usize, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        Err(de::Error::invalid_type(Unexpected::Str(&self.0), &visitor))
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(self, _name: &'static str, _fields: &'static [&'static str], visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_map(visitor)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(self, _name: &'static str, _variants: &'static [&'static str], visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_enum(self.0.trim().to_owned().into_deserializer())
+    }
+
+    fn deserialize_identifier<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_string(self.0)
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_unit()
+    }
+}
+
+/// The child elements sharing a name, a sequence or a single value depending on the field they end up in
+struct ElementGroup(Vec<Node>);
+
+impl ElementGroup {
+    fn single(mut self) -> Result<Deserializer, Error> {
+        if self.0.len() == 1 {
+            Ok(Deserializer::new(self.0.remove(0)))
+        } else {
+            Err(Error::Serde(format!("expected a single element '{}', found {}", self.0[0].get_name(), self.0.len())))
+        }
+    }
+}
+
+macro_rules! forward_to_single {
+    ($($method:ident)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+                self.single()?.$method(visitor)
+            }
+        )*
+    };
+}
+
+impl<'de> de::Deserializer<'de> for ElementGroup {
+    type Error = Error;
+
+    forward_to_single! {
+        deserialize_bool deserialize_i8 deserialize_i16 deserialize_i32 deserialize_i64
+        deserialize_u8 deserialize_u16 deserialize_u32 deserialize_u64 deserialize_f32 deserialize_f64
+        deserialize_char deserialize_str deserialize_string deserialize_bytes deserialize_byte_buf
+        deserialize_unit deserialize_map deserialize_identifier deserialize_ignored_any
+    }
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        if self.0.len() == 1 {
+            self.single()?.deserialize_any(visitor)
+        } else {
+            self.deserialize_seq(visitor)
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(self, name: &'static str, visitor: V) -> Result<V::Value, Error> {
+        self.single()?.deserialize_unit_struct(name, visitor)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_seq(ElementSeq(self.0.into_iter()))
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(self, _name: &'static str, _len: usize, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(self, name: &'static str, fields: &'static [&'static str], visitor: V) -> Result<V::Value, Error> {
+        self.single()?.deserialize_struct(name, fields, visitor)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(self, name: &'static str, variants: &'static [&'static str], visitor: V) -> Result<V::Value, Error> {
+        self.single()?.deserialize_enum(name, variants, visitor)
+    }
+}
+
+enum Entry {
+    Value(String),
+    Elements(Vec<Node>),
+}
+
+/// The attributes, child elements and text of an element as map entries
+struct ElementMap {
+    entries: vec::IntoIter<(String, Entry)>,
+    value: Option<Entry>,
+}
+
+impl ElementMap {
+    fn new(node: &Node) -> ElementMap {
+        let mut entries = Vec::new();
+        let mut attributes: Vec<(String, String)> = node.get_properties().into_iter().collect();
+        attributes.sort();
+        for (name, value) in attributes {
+            entries.push((format!("@{}", name), Entry::Value(value)));
+        }
+
+        let mut groups: Vec<(String, Vec<Node>)> = Vec::new();
+        for child in node.get_child_elements() {
+            let name = child.get_name();
+            match groups.iter().position(|(group_name, _)| *group_name == name) {
+                Some(index) => groups[index].1.push(child),
+                None => groups.push((name, vec![child])),
+            }
+        }
+        entries.extend(groups.into_iter().map(|(name, nodes)| (name, Entry::Elements(nodes))));
+
+        let text = node.get_child_text();
+        if !text.trim().is_empty() {
+            entries.push((String::from("$value"), Entry::Value(text)));
+        }
+        ElementMap { entries: entries.into_iter(), value: None }
+    }
+}
+
+impl<'de> MapAccess<'de> for ElementMap {
+    type Error = Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>, Error> {
+        match self.entries.next() {
+            Some((key, value)) => {
+                self.value = Some(value);
+                seed.deserialize(key.into_deserializer()).map(Some)
+            },
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Error> {
+        match self.value.take() {
+            Some(Entry::Value(value)) => seed.deserialize(ValueDeserializer(value)),
+            Some(Entry::Elements(nodes)) => seed.deserialize(ElementGroup(nodes)),
+            None => Err(Error::Serde(String::from("value requested before its key"))),
+        }
+    }
+}
+
+struct ElementSeq(vec::IntoIter<Node>);
+
+impl<'de> SeqAccess<'de> for ElementSeq {
+    type Error = Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<Option<T::Value>, Error> {
+        match self.0.next() {
+            Some(node) => seed.deserialize(Deserializer::new(node)).map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
+struct ValueSeq(vec::IntoIter<String>);
+
+impl<'de> SeqAccess<'de> for ValueSeq {
+    type Error = Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<Option<T::Value>, Error> {
+        match self.0.next() {
+            Some(value) => seed.deserialize(ValueDeserializer(value)).map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
+/// An enum variant given by the name of an element
+struct ElementEnum(Node);
+
+impl<'de> EnumAccess<'de> for ElementEnum {
+    type Error = Error;
+    type Variant = Deserializer;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(self, seed: V) -> Result<(V::Value, Deserializer), Error> {
+        let name: de::value::StringDeserializer<Error> = self.0.get_name().into_deserializer();
+        Ok((seed.deserialize(name)?, Deserializer::new(self.0)))
+    }
+}
+
+impl<'de> VariantAccess<'de> for Deserializer {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, Error> {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value, Error> {
+        de::Deserializer::deserialize_seq(self, visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(self, _fields: &'static [&'static str], visitor: V) -> Result<V::Value, Error> {
+        de::Deserializer::deserialize_map(self, visitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use super::*;
+
+    #[test]
+    fn from_node_test() {
+        let doc = Document::parse("<root id=\"7\"><item>1</item><item> 2 </item><flag>1</flag>body</root>").unwrap();
+        let root = doc.get_root_element().unwrap();
+
+        let map: HashMap<String, Vec<String>> = from_node(&root).unwrap();
+        assert_eq!(map["@id"], vec!["7"]);
+        assert_eq!(map["item"], vec!["1", " 2 "]);
+        assert_eq!(map["$value"], vec!["body"]);
+
+        let numbers: Vec<u8> = from_node(&root).unwrap();
+        assert_eq!(numbers, vec![1, 2, 1]);
+        assert_eq!(from_node::<bool>(&root.get_elements_by_tag_name("flag")[0]).ok(), Some(true));
+        assert!(from_node::<u32>(&root).is_err());
+    }
+}
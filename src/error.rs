@@ -0,0 +1,83 @@
+use std::error;
+use std::ffi::NulError;
+use std::fmt;
+use std::str::Utf8Error;
+use std::string::FromUtf8Error;
+
+#[cfg(feature = "serde")]
+use serde::{de, ser};
+
+/// Errors of the fallible (`try_*`) accessors and setters, of selector queries and of parsing with a `SecurityPolicy`
+/// More variants may be added without a major release, matches need a wildcard arm.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum Error {
+    /// A string handed to libxml2 contains a NUL byte
+    InteriorNul(usize),
+    /// A string returned by libxml2 is not valid UTF-8
+    InvalidUtf8(Utf8Error),
+    /// A CSS selector could not be compiled or evaluated
+    InvalidSelector(String),
+    /// The content of the node cannot be replaced, such as that of an entity reference
+    ReadOnlyNode,
+    /// The input is larger than `SecurityPolicy::max_document_bytes`
+    DocumentSizeLimit(usize),
+    /// Elements are nested deeper than `SecurityPolicy::max_depth`
+    DepthLimit(usize),
+    /// The document has more elements than `SecurityPolicy::max_nodes`
+    NodeLimit(usize),
+    /// More entities were expanded than `SecurityPolicy::max_entity_expansions`
+    EntityExpansionLimit(usize),
+    /// A value could not be serialized or deserialized, only returned with the `serde` feature
+    Serde(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::InteriorNul(position) => write!(f, "string contains a NUL byte at position {}", position),
+            Error::InvalidUtf8(ref err) => write!(f, "string is not valid UTF-8: {}", err),
+            Error::InvalidSelector(ref message) => write!(f, "invalid selector: {}", message),
+            Error::ReadOnlyNode => f.write_str("node is read-only"),
+            Error::DocumentSizeLimit(limit) => write!(f, "document is larger than {} bytes", limit),
+            Error::DepthLimit(limit) => write!(f, "elements are nested deeper than {} levels", limit),
+            Error::NodeLimit(limit) => write!(f, "document has more than {} elements", limit),
+            Error::EntityExpansionLimit(limit) => write!(f, "more than {} entities expanded", limit),
+            Error::Serde(ref message) => f.write_str(message),
+        }
+    }
+}
+
+impl error::Error for Error {}
+
+#[cfg(feature = "serde")]
+impl de::Error for Error {
+    fn custom<T: fmt::Display>(message: T) -> Error {
+        Error::Serde(message.to_string())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl ser::Error for Error {
+    fn custom<T: fmt::Display>(message: T) -> Error {
+        Error::Serde(message.to_string())
+    }
+}
+
+impl From<NulError> for Error {
+    fn from(err: NulError) -> Error {
+        Error::InteriorNul(err.nul_position())
+    }
+}
+
+impl From<Utf8Error> for Error {
+    fn from(err: Utf8Error) -> Error {
+        Error::InvalidUtf8(err)
+    }
+}
+
+impl From<FromUtf8Error> for Error {
+    fn from(err: FromUtf8Error) -> Error {
+        Error::InvalidUtf8(err.utf8_error())
+    }
+}
@@ -1,18 +1,38 @@
-#![feature(pointer_methods)]
-
 #[macro_use]
 extern crate bitflags;
 extern crate libc;
+#[cfg(feature = "serde")]
+extern crate serde;
 
 #[allow(dead_code, non_camel_case_types, non_upper_case_globals, non_snake_case)]
 mod libxml2;
+pub mod sys;
+
+mod error;
+pub use error::Error;
 
 mod tree;
 pub use tree::ParseOptions;
 
+pub mod xpath;
+pub mod css;
+pub mod catalog;
+
+#[cfg(feature = "serde")]
+pub mod de;
+#[cfg(feature = "serde")]
+pub use de::{from_node, from_document};
+#[cfg(feature = "serde")]
+pub mod ser;
+#[cfg(feature = "serde")]
+pub use ser::{to_document, to_document_with_root};
+
 use tree::{XmlError, XmlInput};
-pub use tree::document::Document;
-pub use tree::node::{Node, NodeType};
+pub use tree::document::{Document, SyncDocument};
+pub use tree::node::{Node, NodeType, Whitespace};
+pub use tree::builder::ElementBuilder;
+pub use tree::fragment::DocumentFragment;
+pub use tree::parser::{Parser, SecurityPolicy};
 
 pub fn xml_with_options<R: XmlInput + ?Sized>(r:&R, url: &str, encoding: &str, options: ParseOptions) -> Result<Document, Vec<XmlError>> {
     Document::parse_with_options(r, url, encoding, options)
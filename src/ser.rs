@@ -0,0 +1,485 @@
+//! Serialization of Rust values into a `Document` with serde
+//!
+//! The mirror image of the `de` module: fields named `@name` become attributes, the field
+//! `$value` becomes the text of the element and all other fields become child elements named
+//! after the field. Sequences become repeated elements, `None` values are left out.
+
+use std::fmt::Display;
+
+use serde::ser::{self, Serialize, Impossible};
+
+use error::Error;
+use tree::document::Document;
+use tree::node::Node;
+
+/// Serializes `value` into a new document, the root element is named after the type of `value`
+pub fn to_document<T: Serialize + ?Sized>(value: &T) -> Result<Document, Error> {
+    serialize_document(value, None)
+}
+
+/// Serializes `value` into a new document with a root element named `root`
+pub fn to_document_with_root<T: Serialize + ?Sized>(value: &T, root: &str) -> Result<Document, Error> {
+    serialize_document(value, Some(root.to_owned()))
+}
+
+fn serialize_document<T: Serialize + ?Sized>(value: &T, name: Option<String>) -> Result<Document, Error> {
+    let mut document = Document::new().map_err(|_| Error::Serde(String::from("cannot create a document")))?;
+    value.serialize(Serializer { parent: Parent::Document(&mut document), name })?;
+    if document.get_root_element().is_none() {
+        return Err(Error::Serde(String::from("the value did not produce a root element")));
+    }
+    Ok(document)
+}
+
+enum Parent<'a> {
+    Document(&'a mut Document),
+    Element(Node),
+}
+
+impl<'a> Parent<'a> {
+    fn reborrow<'b>(&'b mut self) -> Parent<'b> {
+        match *self {
+            Parent::Document(ref mut document) => Parent::Document(document),
+            Parent::Element(ref node) => Parent::Element(node.clone()),
+        }
+    }
+}
+
+/// A serde `Serializer` adding the elements for a value to a parent
+pub struct Serializer<'a> {
+    parent: Parent<'a>,
+    name: Option<String>,
+}
+
+impl<'a> Serializer<'a> {
+    /// Creates a serializer appending the elements named `name` for a value to `parent`
+    pub fn new(parent: &Node, name: &str) -> Serializer<'a> {
+        Serializer { parent: Parent::Element(parent.clone()), name: Some(name.to_owned()) }
+    }
+
+    fn create_element(&mut self, type_name: Option<&str>) -> Result<Node, Error> {
+        let name = match (self.name.as_ref(), type_name) {
+            (Some(name), _) => name.clone(),
+            (None, Some(type_name)) => type_name.to_owned(),
+            (None, None) => return Err(Error::Serde(String::from("the root element needs a name, use to_document_with_root"))),
+        };
+        let doc_ref = match self.parent {
+            Parent::Document(ref document) => document.doc_ref(),
+            Parent::Element(ref node) => node.doc_ref(),
+        };
+        let mut element = Node::new(&name, None, doc_ref).map_err(|_| Error::Serde(format!("cannot create element '{}'", name)))?;
+        match self.parent {
+            Parent::Document(ref mut document) => {
+                if document.get_root_element().is_some() {
+                    return Err(Error::Serde(String::from("a document has a single root element")));
+                }
+                document.set_root_element(&mut element);
+            },
+            Parent::Element(ref mut parent) => {
+                parent.add_child(&mut element).map_err(|_| Error::Serde(format!("cannot add element '{}'", name)))?;
+            },
+        }
+        Ok(element)
+    }
+
+    fn serialize_text<T: Display>(mut self, value: T) -> Result<(), Error> {
+        let mut element = self.create_element(None)?;
+        element.try_append_text(&value.to_string())
+    }
+}
+
+impl<'a> ser::Serializer for Serializer<'a> {
+    type Ok = ();
+    type Error = Error;
+    type SerializeSeq = SeqSerializer<'a>;
+    type SerializeTuple = SeqSerializer<'a>;
+    type SerializeTupleStruct = SeqSerializer<'a>;
+    type SerializeTupleVariant = SeqSerializer<'a>;
+    type SerializeMap = ElementSerializer;
+    type SerializeStruct = ElementSerializer;
+    type SerializeStructVariant = ElementSerializer;
+
+    fn serialize_bool(self, v: bool) -> Result<(), Error> { self.serialize_text(v) }
+    fn serialize_i8(self, v: i8) -> Result<(), Error> { self.serialize_text(v) }
+    fn serialize_i16(self, v: i16) -> Result<(), Error> { self.serialize_text(v) }
+    fn serialize_i32(self, v: i32) -> Result<(), Error> { self.serialize_text(v) }
+    fn serialize_i64(self, v: i64) -> Result<(), Error> { self.serialize_text(v) }
+    fn serialize_u8(self, v: u8) -> Result<(), Error> { self.serialize_text(v) }
+    fn serialize_u16(self, v: u16) -> Result<(), Error> { self.serialize_text(v) }
+    fn serialize_u32(self, v: u32) -> Result<(), Error> { self.serialize_text(v) }
+    fn serialize_u64(self, v: u64) -> Result<(), Error> { self.serialize_text(v) }
+    fn serialize_f32(self, v: f32) -> Result<(), Error> { self.serialize_text(v) }
+    fn serialize_f64(self, v: f64) -> Result<(), Error> { self.serialize_text(v) }
+    fn serialize_char(self, v: char) -> Result<(), Error> { self.serialize_text(v) }
+    fn serialize_str(self, v: &str) -> Result<(), Error> { self.serialize_text(v) }
+
+    fn serialize_bytes(self, _v: &[u8]) -> Result<(), Error> {
+        Err(Error::Serde(String::from("byte arrays are not supported")))
+    }
+
+    fn serialize_none(self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<(), Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(mut self) -> Result<(), Error> {
+        self.create_element(None).map(|_| ())
+    }
+
+    fn serialize_unit_struct(mut self, name: &'static str) -> Result<(), Error> {
+        self.create_element(Some(name)).map(|_| ())
+    }
+
+    /// Becomes the text of the element, like the unit variants read by the `de` module
+    fn serialize_unit_variant(self, _name: &'static str, _index: u32, variant: &'static str) -> Result<(), Error> {
+        self.serialize_text(variant)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(self, _name: &'static str, value: &T) -> Result<(), Error> {
+        value.serialize(self)
+    }
+
+    /// Becomes an element named after the variant inside the element
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(mut self, name: &'static str, _index: u32, variant: &'static str, value: &T) -> Result<(), Error> {
+        let element = self.create_element(Some(name))?;
+        value.serialize(Serializer::new(&element, variant))
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<SeqSerializer<'a>, Error> {
+        Ok(SeqSerializer { serializer: self })
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<SeqSerializer<'a>, Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(self, _name: &'static str, len: usize) -> Result<SeqSerializer<'a>, Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(mut self, name: &'static str, _index: u32, variant: &'static str, _len: usize) -> Result<SeqSerializer<'a>, Error> {
+        let element = self.create_element(Some(name))?;
+        Ok(SeqSerializer { serializer: Serializer::new(&element, variant) })
+    }
+
+    fn serialize_map(mut self, _len: Option<usize>) -> Result<ElementSerializer, Error> {
+        let element = self.create_element(None)?;
+        Ok(ElementSerializer { element, key: None })
+    }
+
+    fn serialize_struct(mut self, name: &'static str, _len: usize) -> Result<ElementSerializer, Error> {
+        let element = self.create_element(Some(name))?;
+        Ok(ElementSerializer { element, key: None })
+    }
+
+    fn serialize_struct_variant(mut self, name: &'static str, _index: u32, variant: &'static str, _len: usize) -> Result<ElementSerializer, Error> {
+        let element = self.create_element(Some(name))?;
+        let element = Serializer::new(&element, variant).create_element(None)?;
+        Ok(ElementSerializer { element, key: None })
+    }
+}
+
+/// Serializes the items of a sequence as repeated elements
+pub struct SeqSerializer<'a> {
+    serializer: Serializer<'a>,
+}
+
+impl<'a> SeqSerializer<'a> {
+    fn serialize_item<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        value.serialize(Serializer { parent: self.serializer.parent.reborrow(), name: self.serializer.name.clone() })
+    }
+}
+
+impl<'a> ser::SerializeSeq for SeqSerializer<'a> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.serialize_item(value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl<'a> ser::SerializeTuple for SeqSerializer<'a> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.serialize_item(value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl<'a> ser::SerializeTupleStruct for SeqSerializer<'a> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.serialize_item(value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl<'a> ser::SerializeTupleVariant for SeqSerializer<'a> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.serialize_item(value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+/// Serializes the fields of a struct or the entries of a map into an element
+pub struct ElementSerializer {
+    element: Node,
+    key: Option<String>,
+}
+
+impl ElementSerializer {
+    fn serialize_entry<T: Serialize + ?Sized>(&mut self, key: &str, value: &T) -> Result<(), Error> {
+        if let Some(name) = key.strip_prefix('@') {
+            if let Some(text) = value.serialize(TextSerializer)? {
+                self.element.try_set_property(name, &text)?;
+            }
+            Ok(())
+        } else if key == "$value" {
+            if let Some(text) = value.serialize(TextSerializer)? {
+                self.element.try_append_text(&text)?;
+            }
+            Ok(())
+        } else {
+            value.serialize(Serializer::new(&self.element, key))
+        }
+    }
+}
+
+impl ser::SerializeStruct for ElementSerializer {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, key: &'static str, value: &T) -> Result<(), Error> {
+        self.serialize_entry(key, value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeStructVariant for ElementSerializer {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, key: &'static str, value: &T) -> Result<(), Error> {
+        self.serialize_entry(key, value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeMap for ElementSerializer {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), Error> {
+        match key.serialize(TextSerializer)? {
+            Some(key) => {
+                self.key = Some(key);
+                Ok(())
+            },
+            None => Err(Error::Serde(String::from("map keys must not be None"))),
+        }
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        match self.key.take() {
+            Some(key) => self.serialize_entry(&key, value),
+            None => Err(Error::Serde(String::from("map value serialized before its key"))),
+        }
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+/// Serializes scalar values to the text of attributes and elements, sequences become whitespace separated lists
+struct TextSerializer;
+
+fn not_text(kind: &str) -> Error {
+    Error::Serde(format!("{} cannot be serialized as text", kind))
+}
+
+impl ser::Serializer for TextSerializer {
+    type Ok = Option<String>;
+    type Error = Error;
+    type SerializeSeq = ListSerializer;
+    type SerializeTuple = ListSerializer;
+    type SerializeTupleStruct = ListSerializer;
+    type SerializeTupleVariant = Impossible<Option<String>, Error>;
+    type SerializeMap = Impossible<Option<String>, Error>;
+    type SerializeStruct = Impossible<Option<String>, Error>;
+    type SerializeStructVariant = Impossible<Option<String>, Error>;
+
+    fn serialize_bool(self, v: bool) -> Result<Option<String>, Error> { Ok(Some(v.to_string())) }
+    fn serialize_i8(self, v: i8) -> Result<Option<String>, Error> { Ok(Some(v.to_string())) }
+    fn serialize_i16(self, v: i16) -> Result<Option<String>, Error> { Ok(Some(v.to_string())) }
+    fn serialize_i32(self, v: i32) -> Result<Option<String>, Error> { Ok(Some(v.to_string())) }
+    fn serialize_i64(self, v: i64) -> Result<Option<String>, Error> { Ok(Some(v.to_string())) }
+    fn serialize_u8(self, v: u8) -> Result<Option<String>, Error> { Ok(Some(v.to_string())) }
+    fn serialize_u16(self, v: u16) -> Result<Option<String>, Error> { Ok(Some(v.to_string())) }
+    fn serialize_u32(self, v: u32) -> Result<Option<String>, Error> { Ok(Some(v.to_string())) }
+    fn serialize_u64(self, v: u64) -> Result<Option<String>, Error> { Ok(Some(v.to_string())) }
+    fn serialize_f32(self, v: f32) -> Result<Option<String>, Error> { Ok(Some(v.to_string())) }
+    fn serialize_f64(self, v: f64) -> Result<Option<String>, Error> { Ok(Some(v.to_string())) }
+    fn serialize_char(self, v: char) -> Result<Option<String>, Error> { Ok(Some(v.to_string())) }
+    fn serialize_str(self, v: &str) -> Result<Option<String>, Error> { Ok(Some(v.to_owned())) }
+
+    fn serialize_bytes(self, _v: &[u8]) -> Result<Option<String>, Error> {
+        Err(Error::Serde(String::from("byte arrays are not supported")))
+    }
+
+    fn serialize_none(self) -> Result<Option<String>, Error> {
+        Ok(None)
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Option<String>, Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Option<String>, Error> {
+        Ok(Some(String::new()))
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Option<String>, Error> {
+        Ok(Some(String::new()))
+    }
+
+    fn serialize_unit_variant(self, _name: &'static str, _index: u32, variant: &'static str) -> Result<Option<String>, Error> {
+        Ok(Some(variant.to_owned()))
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(self, _name: &'static str, value: &T) -> Result<Option<String>, Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(self, _name: &'static str, _index: u32, _variant: &'static str, _value: &T) -> Result<Option<String>, Error> {
+        Err(not_text("an enum variant with data"))
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<ListSerializer, Error> {
+        Ok(ListSerializer(Vec::new()))
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<ListSerializer, Error> {
+        Ok(ListSerializer(Vec::new()))
+    }
+
+    fn serialize_tuple_struct(self, _name: &'static str, _len: usize) -> Result<ListSerializer, Error> {
+        Ok(ListSerializer(Vec::new()))
+    }
+
+    fn serialize_tuple_variant(self, _name: &'static str, _index: u32, _variant: &'static str, _len: usize) -> Result<Self::SerializeTupleVariant, Error> {
+        Err(not_text("an enum variant with data"))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Error> {
+        Err(not_text("a map"))
+    }
+
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeStruct, Error> {
+        Err(not_text("a struct"))
+    }
+
+    fn serialize_struct_variant(self, _name: &'static str, _index: u32, _variant: &'static str, _len: usize) -> Result<Self::SerializeStructVariant, Error> {
+        Err(not_text("an enum variant with data"))
+    }
+}
+
+struct ListSerializer(Vec<String>);
+
+impl ListSerializer {
+    fn push<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        if let Some(text) = value.serialize(TextSerializer)? {
+            self.0.push(text);
+        }
+        Ok(())
+    }
+}
+
+impl ser::SerializeSeq for ListSerializer {
+    type Ok = Option<String>;
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Option<String>, Error> {
+        Ok(Some(self.0.join(" ")))
+    }
+}
+
+impl ser::SerializeTuple for ListSerializer {
+    type Ok = Option<String>;
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Option<String>, Error> {
+        Ok(Some(self.0.join(" ")))
+    }
+}
+
+impl ser::SerializeTupleStruct for ListSerializer {
+    type Ok = Option<String>;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Option<String>, Error> {
+        Ok(Some(self.0.join(" ")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+    use super::*;
+
+    #[test]
+    fn to_document_test() {
+        let mut map = BTreeMap::new();
+        map.insert("@id", vec![1, 2]);
+        map.insert("item", vec![3, 4]);
+        map.insert("$value", vec![5]);
+        let doc = to_document_with_root(&map, "root").unwrap();
+        assert_eq!(doc.get_root_element().unwrap().to_string(false), "<root id=\"1 2\">5<item>3</item><item>4</item></root>");
+
+        assert!(to_document(&map).is_err());
+        assert!(to_document(&vec![1, 2]).is_err());
+        assert!(to_document(&None::<u32>).is_err());
+    }
+}
@@ -0,0 +1,11 @@
+//! Raw bindings to the whole libxml2 API, generated by bindgen from the installed headers.
+//!
+//! Everything in here is unsafe to use and follows the ownership rules of libxml2.
+//! `Document::as_ptr` and `Node::as_ptr` hand out the pointers behind the safe types,
+//! `Document::from_raw` and `Node::from_raw` wrap pointers obtained through these bindings.
+//!
+//! The `_private` fields of the nodes of a `Document` point to the state shared by their `Node` handles,
+//! and the crate registers a deregister node callback (`xmlDeregisterNodeDefault`) to learn about freed nodes.
+//! Neither may be changed while documents are in use.
+
+pub use libxml2::*;
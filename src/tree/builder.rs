@@ -0,0 +1,306 @@
+use std::ffi::CString;
+use std::ptr;
+
+use libxml2::{xmlNsPtr, xmlNewNs, xmlSetNs, xmlSearchNs, xmlSetNsProp};
+
+use tree::xml_bytes;
+use tree::document::Document;
+use tree::node::Node;
+
+/// Describes an element with its attributes, namespaces and content,
+/// to be built into a `Document` in one go.
+///
+/// ```ignore
+/// let root = ElementBuilder::new("config")
+///     .attr("version", "2")
+///     .child(ElementBuilder::new("name").text("example"))
+///     .build(&doc)?;
+/// ```
+#[derive(Debug, Clone)]
+pub struct ElementBuilder {
+    name: String,
+    // The namespace of the element, as index into `declarations`
+    namespace: Option<usize>,
+    declarations: Vec<(Option<String>, String)>,
+    attributes: Vec<(String, String)>,
+    content: Vec<Content>,
+}
+
+#[derive(Debug, Clone)]
+enum Content {
+    Element(ElementBuilder),
+    Text(String),
+}
+
+impl ElementBuilder {
+    /// Starts an element named `name`
+    pub fn new(name: &str) -> ElementBuilder {
+        ElementBuilder {
+            name: name.to_owned(),
+            namespace: None,
+            declarations: Vec::new(),
+            attributes: Vec::new(),
+            content: Vec::new(),
+        }
+    }
+
+    /// Adds the attribute `name`, a prefixed name puts it into the namespace declared with that prefix.
+    /// `xmlns` and `xmlns:prefix` declare namespaces instead, `xmlns` also puts the element into the namespace.
+    pub fn attr(mut self, name: &str, value: &str) -> ElementBuilder {
+        if name == "xmlns" {
+            return self.ns(None, value);
+        }
+        if let Some(prefix) = name.strip_prefix("xmlns:") {
+            self.declarations.push((Some(prefix.to_owned()), value.to_owned()));
+        } else {
+            self.attributes.push((name.to_owned(), value.to_owned()));
+        }
+        self
+    }
+
+    /// Appends text to the content
+    pub fn text(mut self, text: &str) -> ElementBuilder {
+        self.content.push(Content::Text(text.to_owned()));
+        self
+    }
+
+    /// Appends a child element to the content
+    pub fn child(mut self, child: ElementBuilder) -> ElementBuilder {
+        self.content.push(Content::Element(child));
+        self
+    }
+
+    /// Puts the element into the namespace `href` with `prefix`, or into the default namespace `href`.
+    /// The namespace is declared on the element unless an ancestor already declares it with the same prefix.
+    /// Without a namespace of its own, an element is in the default namespace of its parent.
+    pub fn ns(mut self, prefix: Option<&str>, href: &str) -> ElementBuilder {
+        self.namespace = Some(self.declarations.len());
+        self.declarations.push((prefix.map(|p| p.to_owned()), href.to_owned()));
+        self
+    }
+
+    /// Builds the element with all its content in `document`, failing for an attribute prefix without namespace in scope.
+    /// The element is owned by the document until it is inserted into the tree.
+    pub fn build(&self, document: &Document) -> Result<Node, ()> {
+        let mut element = Node::new(&self.name, None, document.doc_ref())?;
+        self.build_into(&mut element, document)?;
+        Ok(element)
+    }
+
+    /// Builds the element and makes it the root element of `document`
+    pub fn build_root(&self, document: &mut Document) -> Result<Node, ()> {
+        let mut root = self.build(document)?;
+        document.set_root_element(&mut root);
+        Ok(root)
+    }
+
+    fn build_into(&self, element: &mut Node, document: &Document) -> Result<(), ()> {
+        let mut ns_ptr = ptr::null_mut();
+        for (index, (prefix, href)) in self.declarations.iter().enumerate() {
+            let prefix = prefix.as_ref().map(|p| p.as_str());
+            if self.namespace == Some(index) {
+                ns_ptr = match find_namespace(element, document, prefix, href)? {
+                    Some(in_scope) => in_scope,
+                    None => declare_namespace(element, prefix, href)?,
+                };
+            } else {
+                declare_namespace(element, prefix, href)?;
+            }
+        }
+        if self.namespace.is_none() {
+            // Null if there is no default namespace in scope
+            ns_ptr = unsafe { xmlSearchNs(document.doc_ptr(), element.node_ptr_mut(), ptr::null()) };
+        }
+        if !ns_ptr.is_null() {
+            unsafe { xmlSetNs(element.node_ptr_mut(), ns_ptr) };
+        }
+
+        for (name, value) in &self.attributes {
+            set_attribute(element, document, name, value)?;
+        }
+        for content in &self.content {
+            match *content {
+                Content::Text(ref text) => element.try_append_text(text).map_err(|_| ())?,
+                Content::Element(ref builder) => {
+                    let mut child = Node::new(&builder.name, None, document.doc_ref())?;
+                    element.add_child(&mut child)?;
+                    // Inserted first, so the namespaces of the ancestors are in scope
+                    builder.build_into(&mut child, document)?;
+                },
+            }
+        }
+        Ok(())
+    }
+}
+
+fn c_prefix(prefix: Option<&str>) -> Result<Option<CString>, ()> {
+    match prefix {
+        Some(prefix) => CString::new(prefix).map(Some).map_err(|_| ()),
+        None => Ok(None),
+    }
+}
+
+// The namespace declared with `prefix` in scope of `element`, if it has the URI `href`
+fn find_namespace(element: &mut Node, document: &Document, prefix: Option<&str>, href: &str) -> Result<Option<xmlNsPtr>, ()> {
+    let c_prefix = c_prefix(prefix)?;
+    let prefix_ptr = c_prefix.as_ref().map_or(ptr::null(), |p| p.as_ptr() as *const u8);
+    unsafe {
+        let ns_ptr = xmlSearchNs(document.doc_ptr(), element.node_ptr_mut(), prefix_ptr);
+        if !ns_ptr.is_null() && !(*ns_ptr).href.is_null() && xml_bytes((*ns_ptr).href) == href.as_bytes() {
+            Ok(Some(ns_ptr))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+// Sets the attribute `name`, its prefix has to be declared in scope of `element`
+fn set_attribute(element: &mut Node, document: &Document, name: &str, value: &str) -> Result<(), ()> {
+    let (prefix, local_name) = match name.find(':') {
+        Some(index) => (&name[..index], &name[index + 1..]),
+        None => return element.try_set_property(name, value).map_err(|_| ()),
+    };
+    let c_prefix = CString::new(prefix).map_err(|_| ())?;
+    let c_name = CString::new(local_name).map_err(|_| ())?;
+    let c_value = CString::new(value).map_err(|_| ())?;
+    unsafe {
+        // Also finds the `xml` prefix, which is always in scope
+        let ns_ptr = xmlSearchNs(document.doc_ptr(), element.node_ptr_mut(), c_prefix.as_ptr() as *const u8);
+        if ns_ptr.is_null() {
+            return Err(());
+        }
+        let attr_ptr = xmlSetNsProp(element.node_ptr_mut(), ns_ptr, c_name.as_ptr() as *const u8, c_value.as_ptr() as *const u8);
+        if attr_ptr.is_null() {
+            Err(())
+        } else {
+            Ok(())
+        }
+    }
+}
+
+fn declare_namespace(element: &mut Node, prefix: Option<&str>, href: &str) -> Result<xmlNsPtr, ()> {
+    let c_href = CString::new(href).map_err(|_| ())?;
+    let c_prefix = c_prefix(prefix)?;
+    let prefix_ptr = c_prefix.as_ref().map_or(ptr::null(), |p| p.as_ptr() as *const u8);
+    let ns_ptr = unsafe { xmlNewNs(element.node_ptr_mut(), c_href.as_ptr() as *const u8, prefix_ptr) };
+    if ns_ptr.is_null() {
+        Err(())
+    } else {
+        Ok(ns_ptr)
+    }
+}
+
+/// Builds a tree of elements in a `Document` and returns the root `Node`, not yet inserted into the tree.
+///
+/// Elements are written as `name(attribute = value, ...) { content }`, the attribute list is optional.
+/// Element and attribute names are identifiers or string literals, values are any expression implementing `Display`.
+/// The content is a sequence of child elements, string literals and `(expression)` for computed text.
+///
+/// ```ignore
+/// let root = xml!(doc => config(version = 2, "xml:lang" = "en") {
+///     name { "example" }
+///     server(port = 80) { (host) }
+/// }).unwrap();
+/// ```
+#[macro_export]
+macro_rules! xml {
+    ($document:expr => $($element:tt)+) => {
+        $crate::__xml_element!($($element)+).build(&$document)
+    };
+}
+
+#[macro_export]
+#[doc(hidden)]
+macro_rules! __xml_element {
+    (@element $name:expr; ( $($key:tt = $value:expr),* ) { $($content:tt)* }) => {
+        $crate::__xml_element!(@content $crate::ElementBuilder::new($name)
+                       $(.attr($crate::__xml_element!(@name $key), &$value.to_string()))*; $($content)*)
+    };
+    (@element $name:expr; { $($content:tt)* }) => {
+        $crate::__xml_element!(@content $crate::ElementBuilder::new($name); $($content)*)
+    };
+
+    (@name $key:ident) => { stringify!($key) };
+    (@name $key:expr) => { $key };
+
+    (@content $builder:expr; ) => { $builder };
+    // A string literal followed by attributes or content is an element, otherwise text
+    (@content $builder:expr; $name:literal ( $($attributes:tt)* ) { $($content:tt)* } $($rest:tt)*) => {
+        $crate::__xml_element!(@content $builder.child($crate::__xml_element!($name ( $($attributes)* ) { $($content)* })); $($rest)*)
+    };
+    (@content $builder:expr; $name:literal { $($content:tt)* } $($rest:tt)*) => {
+        $crate::__xml_element!(@content $builder.child($crate::__xml_element!($name { $($content)* })); $($rest)*)
+    };
+    (@content $builder:expr; $text:literal $($rest:tt)*) => {
+        $crate::__xml_element!(@content $builder.text($text); $($rest)*)
+    };
+    (@content $builder:expr; ( $text:expr ) $($rest:tt)*) => {
+        $crate::__xml_element!(@content $builder.text(&$text.to_string()); $($rest)*)
+    };
+    (@content $builder:expr; $name:ident ( $($attributes:tt)* ) { $($content:tt)* } $($rest:tt)*) => {
+        $crate::__xml_element!(@content $builder.child($crate::__xml_element!($name ( $($attributes)* ) { $($content)* })); $($rest)*)
+    };
+    (@content $builder:expr; $name:ident { $($content:tt)* } $($rest:tt)*) => {
+        $crate::__xml_element!(@content $builder.child($crate::__xml_element!($name { $($content)* })); $($rest)*)
+    };
+
+    ($name:ident $($rest:tt)+) => { $crate::__xml_element!(@element stringify!($name); $($rest)+) };
+    ($name:literal $($rest:tt)+) => { $crate::__xml_element!(@element $name; $($rest)+) };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn element_builder_test() {
+        let mut doc = Document::new().unwrap();
+        let root = ElementBuilder::new("root")
+            .ns(None, "urn:root")
+            .attr("xmlns:o", "urn:other")
+            .attr("id", "1")
+            .child(ElementBuilder::new("child").text("a & b"))
+            .child(ElementBuilder::new("other").ns(Some("o"), "urn:other"))
+            .build_root(&mut doc)
+            .unwrap();
+        assert_eq!(doc.get_root_element().unwrap(), root);
+        assert_eq!(root.to_string(false),
+                   "<root xmlns=\"urn:root\" xmlns:o=\"urn:other\" id=\"1\"><child>a &amp; b</child><o:other/></root>");
+        let children = root.get_child_elements();
+        assert_eq!(children[0].get_namespace_uri(), Some(String::from("urn:root")));
+        assert_eq!(children[1].get_namespace_uri(), Some(String::from("urn:other")));
+    }
+
+    #[test]
+    fn xml_macro_test() {
+        let doc = Document::new().unwrap();
+        let host = "example.com";
+        let root = xml!(doc => config(version = 2, "xml:lang" = "en") {
+            name { "example" }
+            server(port = 80) { (host) }
+            empty {}
+            "tail"
+        }).unwrap();
+        assert_eq!(root.to_string(false),
+                   "<config version=\"2\" xml:lang=\"en\"><name>example</name><server port=\"80\">example.com</server><empty/>tail</config>");
+        assert!(doc.get_root_element().is_none());
+
+        let root = xml!(doc => "data-list"("data-id" = 1) { "list-item" { "a" } "text" ("b") }).unwrap();
+        assert_eq!(root.to_string(false), "<data-list data-id=\"1\"><list-item>a</list-item>textb</data-list>");
+    }
+
+    #[test]
+    fn prefixed_attribute_test() {
+        let doc = Document::new().unwrap();
+        let root = ElementBuilder::new("root")
+            .attr("xmlns:x", "urn:x")
+            .child(ElementBuilder::new("child").attr("x:lang", "en"))
+            .build(&doc)
+            .unwrap();
+        let child = root.get_first_child().unwrap();
+        let attribute = child.get_property_node("lang").unwrap();
+        assert_eq!(attribute.get_namespace_uri(), Some(String::from("urn:x")));
+        assert_eq!(child.to_string(false), "<child x:lang=\"en\"/>");
+        assert!(ElementBuilder::new("root").attr("y:lang", "en").build(&doc).is_err());
+    }
+}
@@ -1,34 +1,35 @@
 use std::cell::RefCell;
 use std::rc::Rc;
-use std::collections::HashMap;
-use std::ffi::{ CString, CStr };
-use std::mem;
+use std::sync::{Arc, Mutex};
+use std::collections::{BTreeSet, HashSet};
+use std::ffi::CString;
 use std::ptr;
-use std::str;
-use std::os::raw::{c_void, c_int};
+use std::os::raw::c_int;
+
+use libc;
 
 use libxml2::{xmlBufferCreate,
               xmlBufferContent,
               xmlFreeDoc,
               xmlBufferFree,
-              xmlReadMemory,
-              xmlReadFile,
-              xmlResetLastError,
-              xmlSetStructuredErrorFunc,
               xmlDocGetRootElement,
               xmlDocSetRootElement,
               xmlDocDumpMemoryEnc,
               xmlDocDumpFormatMemoryEnc,
-              xmlNodeDump,
               xmlDocCopyNode,
-              xmlUnlinkNode,
+              xmlCopyDoc,
+              xmlGetID,
+              xmlFreeNode,
               xmlSaveFile,
               xmlNewDoc,
               xmlDocPtr,
               xmlNodePtr};
 
-use tree::{ParseOptions, XmlInput, XmlError, error_vec_pusher};
-use tree::node::{Node};
+use error::Error;
+use tree::{ParseOptions, XmlInput, XmlError, init_parser, xml_bytes};
+use tree::parser::Parser;
+use tree::node::{Node, has_handles, subtree_root};
+use tree::fragment::DocumentFragment;
 
 pub type DocumentRef = Rc<RefCell<_Document>>;
 
@@ -37,13 +38,54 @@ pub struct _Document {
     // TODO: How to make public only in this package?
     pub doc_ptr: xmlDocPtr,
     errors: Vec<XmlError>,
-    pub nodes: HashMap<xmlNodePtr, Node>,
+    // Nodes that are bound to this document but not part of its tree.
+    // They are owned by the document and freed together with it.
+    unlinked: HashSet<xmlNodePtr>,
+}
+
+// The documents owned by a `_Document`, the `_private` fields of their nodes are reserved for `Node` handles
+static MANAGED: Mutex<BTreeSet<usize>> = Mutex::new(BTreeSet::new());
+
+/// Returns true iff `doc_ptr` is owned by a `Document`
+pub(crate) fn is_managed(doc_ptr: xmlDocPtr) -> bool {
+    MANAGED.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).contains(&(doc_ptr as usize))
+}
+
+fn set_managed(doc_ptr: xmlDocPtr, managed: bool) {
+    let mut documents = MANAGED.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    if managed {
+        documents.insert(doc_ptr as usize);
+    } else {
+        documents.remove(&(doc_ptr as usize));
+    }
 }
 
 impl _Document {
-    pub fn insert_node(&mut self, node_ptr: xmlNodePtr, node: Node) {
-        // TODO: check that _Node.document is self
-        self.nodes.insert(node_ptr, node);
+    fn new(doc_ptr: xmlDocPtr, errors: Vec<XmlError>) -> _Document {
+        init_parser();
+        set_managed(doc_ptr, true);
+        _Document { doc_ptr, errors, unlinked: HashSet::new() }
+    }
+
+    /// Take ownership of a node that has been unlinked from the tree
+    pub fn insert_unlinked(&mut self, node_ptr: xmlNodePtr) {
+        self.unlinked.insert(node_ptr);
+    }
+
+    /// Release ownership of a node that has been (re-)inserted into the tree
+    pub fn remove_unlinked(&mut self, node_ptr: xmlNodePtr) {
+        self.unlinked.remove(&node_ptr);
+    }
+
+    /// Frees the unlinked subtree `node_ptr` belongs to, unless a handle still refers to one of its nodes
+    pub fn collect_unlinked(&mut self, node_ptr: xmlNodePtr) {
+        unsafe {
+            let root_ptr = subtree_root(node_ptr);
+            if self.unlinked.contains(&root_ptr) && !has_handles(root_ptr) {
+                self.unlinked.remove(&root_ptr);
+                xmlFreeNode(root_ptr);
+            }
+        }
     }
 }
 
@@ -55,6 +97,14 @@ impl Drop for _Document {
     fn drop(&mut self) {
         let doc_ptr = self.doc_ptr;
         unsafe {
+            // Only free the roots of unlinked subtrees, everything below them goes with them.
+            let roots: Vec<xmlNodePtr> = self.unlinked.drain()
+                .filter(|&node_ptr| (*node_ptr).parent.is_null())
+                .collect();
+            for node_ptr in roots {
+                xmlFreeNode(node_ptr);
+            }
+            set_managed(doc_ptr, false);
             xmlFreeDoc(doc_ptr);
         }
     }
@@ -64,13 +114,14 @@ impl Document {
 
     /// Creates a new empty libxml2 document
     pub fn new() -> Result<Self, ()> {
+        init_parser();
         unsafe {
             let c_version = CString::new("1.0").unwrap();
             let doc_ptr = xmlNewDoc(c_version.as_ptr() as *const u8);
             if doc_ptr.is_null() {
                 Err(())
             } else {
-                let doc = _Document{doc_ptr: doc_ptr, errors: vec![], nodes: HashMap::new()};
+                let doc = _Document::new(doc_ptr, vec![]);
                 Ok(Document(Rc::new(RefCell::new(doc))))
             }
         }
@@ -84,13 +135,33 @@ impl Document {
         self.0.borrow().doc_ptr
     }
 
-    fn ptr_as_option(&mut self, node_ptr: xmlNodePtr) -> Option<Node> {
+    /// Returns the underlying `xmlDocPtr` for use with `sys`.
+    /// The document keeps ownership, the pointer is valid as long as the document lives.
+    pub fn as_ptr(&self) -> xmlDocPtr {
+        self.doc_ptr()
+    }
+
+    /// Takes ownership of a document created through `sys`, it is freed together with the `Document`.
+    /// Fails for a null pointer.
+    ///
+    /// # Safety
+    ///
+    /// `doc_ptr` has to point to a valid document which is neither freed nor owned anywhere else.
+    /// The `_private` fields of the document and its nodes have to be null, they are used by `Node` handles.
+    pub unsafe fn from_raw(doc_ptr: xmlDocPtr) -> Result<Document, ()> {
+        if doc_ptr.is_null() {
+            Err(())
+        } else {
+            let doc = _Document::new(doc_ptr, vec![]);
+            Ok(Document(Rc::new(RefCell::new(doc))))
+        }
+    }
+
+    fn ptr_as_option(&self, node_ptr: xmlNodePtr) -> Option<Node> {
         if node_ptr.is_null() {
             None
         } else {
-            let new_node = Node::wrap(node_ptr, self.0.clone());
-            self.0.borrow_mut().insert_node(node_ptr, new_node.clone());
-            Some(new_node)
+            Some(Node::wrap(node_ptr, self.0.clone()))
         }
     }
 
@@ -99,36 +170,94 @@ impl Document {
         let new_node_ptr = unsafe {
             xmlDocCopyNode(node.node_ptr(), self.doc_ptr(), 1)
         };
+        if !new_node_ptr.is_null() {
+            self.0.borrow_mut().insert_unlinked(new_node_ptr);
+        }
         self.ptr_as_option(new_node_ptr)
     }
 
 
+    /// Creates an empty `DocumentFragment`, to collect nodes which are then inserted into the tree at once
+    pub fn create_fragment(&self) -> Result<DocumentFragment, ()> {
+        DocumentFragment::new(self)
+    }
+
+    /// Creates an independent copy of the whole document, including its DTD and root element.
+    /// Nodes that are currently unlinked from the tree are not copied.
+    pub fn deep_clone(&self) -> Result<Document, ()> {
+        let doc_ptr = unsafe { xmlCopyDoc(self.doc_ptr(), 1) };
+        if doc_ptr.is_null() {
+            Err(())
+        } else {
+            let doc = _Document::new(doc_ptr, vec![]);
+            Ok(Document(Rc::new(RefCell::new(doc))))
+        }
+    }
+
+    /// Returns the URL the document was loaded from, if known
+    pub fn url(&self) -> Option<String> {
+        let url_ptr = unsafe { (*self.doc_ptr()).URL };
+        if url_ptr.is_null() {
+            None
+        } else {
+            Some(String::from_utf8_lossy(&unsafe { xml_bytes(url_ptr) }).into_owned())
+        }
+    }
+
+    /// Returns the document node, the parent of the root element
+    pub fn as_node(&self) -> Node {
+        Node::wrap(self.doc_ptr() as xmlNodePtr, self.doc_ref())
+    }
+
     /// Get the root element of the document
     pub fn get_root_element(&self) -> Option<Node> {
+        let node_ptr = unsafe { xmlDocGetRootElement(self.doc_ptr()) };
+        self.ptr_as_option(node_ptr)
+    }
+
+    /// Returns the element carrying the ID `id`, from libxml2's ID table.
+    /// `xml:id` attributes and attributes declared as `ID` in the DTD are registered while parsing.
+    pub fn get_element_by_id(&self, id: &str) -> Option<Node> {
+        let c_id = CString::new(id).ok()?;
         unsafe {
-            let node_ptr = xmlDocGetRootElement(self.0.borrow().doc_ptr);
-            if node_ptr.is_null() {
-                None
-            } else {
-                let node = Node::wrap(node_ptr, self.0.clone());
-                self.0.borrow_mut().nodes.insert(node_ptr, node.clone());
-                Some(node)
+            let attr_ptr = xmlGetID(self.doc_ptr(), c_id.as_ptr() as *const u8);
+            if attr_ptr.is_null() {
+                return None;
             }
+            self.ptr_as_option((*attr_ptr).parent)
         }
     }
 
     /// Sets the root element of the document
+    /// The previous root element, if any, stays owned by the document until it is inserted somewhere else,
+    /// or is freed if there is no handle to it.
+    /// Nothing happens if `root` is bound to another document.
     pub fn set_root_element(&mut self, root: &mut Node) {
-        unsafe {
-            // TODO: returns old root if any, should we do something with that?
-            xmlDocSetRootElement(self.doc_ptr(), root.node_ptr());
+        if !Rc::ptr_eq(&self.0, &root.doc_ref()) {
+            return;
+        }
+        let old_root_ptr = unsafe { xmlDocSetRootElement(self.doc_ptr(), root.node_ptr()) };
+        let mut doc = self.0.borrow_mut();
+        doc.remove_unlinked(root.node_ptr());
+        if !old_root_ptr.is_null() && old_root_ptr != root.node_ptr() {
+            doc.insert_unlinked(old_root_ptr);
+            doc.collect_unlinked(old_root_ptr);
         }
     }
 
+    /// Serializes the document (invalid UTF-8 is replaced)
     pub fn to_string(&self, format: bool) -> String {
+        String::from_utf8_lossy(&self.dump_bytes(format)).into_owned()
+    }
+
+    /// Serializes the document, failing on invalid UTF-8
+    pub fn try_to_string(&self, format: bool) -> Result<String, Error> {
+        Ok(String::from_utf8(self.dump_bytes(format))?)
+    }
+
+    fn dump_bytes(&self, format: bool) -> Vec<u8> {
         unsafe {
             // allocate a buffer to dump into
-            //let mut receiver = ptr::null_mut();
             let mut receiver = ptr::null_mut();
             let mut size: c_int = 0;
             let c_utf8 = CString::new("UTF-8").unwrap();
@@ -139,16 +268,15 @@ impl Document {
                 xmlDocDumpFormatMemoryEnc(doc_ptr, &mut receiver, &mut size, c_utf8.as_ptr(), 1);
             }
 
-            let c_string = CStr::from_ptr(receiver as *const i8);
-            let node_string = str::from_utf8(c_string.to_bytes()).unwrap().to_owned();
-            mem::forget(receiver);
-            node_string
+            let doc_bytes = xml_bytes(receiver);
+            libc::free(receiver as *mut libc::c_void);
+            doc_bytes
         }
     }
 
     /// Write document to `filename`
     pub fn save_file(&self, filename: &str) -> Result<c_int, ()> {
-        let c_filename = CString::new(filename).unwrap();
+        let c_filename = CString::new(filename).map_err(|_| ())?;
         unsafe {
             let retval = xmlSaveFile(c_filename.as_ptr(), self.doc_ptr());
             if retval < 0 {
@@ -163,41 +291,14 @@ impl Document {
     }
 
     pub fn parse_with_options<R: XmlInput + ?Sized>(r:&R, url: &str, encoding: &str, options: ParseOptions) -> Result<Document, Vec<XmlError>> {
-        match r.is_path() {
-            true => Document::parse_file(&r.data(), encoding, options),
-            false => Document::parse_string(&r.data(), url, encoding, options)
-        }
-    }
-
-    fn parse_string(xml_str: &str, url: &str, encoding: &str, options: ParseOptions) -> Result<Document, Vec<XmlError>> {
-        let c_string_len = xml_str.len() as i32;
-        let c_string = CString::new(xml_str).unwrap();
-        let c_utf8 = CString::new(encoding).unwrap();
-        let c_url = CString::new(url).unwrap();
-        Document::parse_handler(|| unsafe { xmlReadMemory(c_string.as_ptr(), c_string_len, c_url.as_ptr(), c_utf8.as_ptr(), options.bits as i32) })
-    }
-
-    fn parse_file(filename: &str, encoding: &str, options: ParseOptions) -> Result<Document, Vec<XmlError>> {
-        let c_filename = CString::new(filename).unwrap();
-        let c_utf8 = CString::new(encoding).unwrap();
-
-        Document::parse_handler(|| unsafe { xmlReadFile(c_filename.as_ptr(), c_utf8.as_ptr(), options.bits as i32) })
-    }
-
-    fn parse_handler<F>(parse_closure: F) -> Result<Document, Vec<XmlError>> where F: Fn() -> xmlDocPtr {
-        unsafe {
-            let errors: Box<Vec<XmlError>> = Box::new(vec![]);
-            xmlResetLastError();
-            let errors_ptr: *mut c_void = mem::transmute(errors);
-            xmlSetStructuredErrorFunc(errors_ptr, Some(error_vec_pusher));
-            let doc_ptr = parse_closure();
-            xmlSetStructuredErrorFunc(ptr::null_mut(), None);
-            Document::handle_result_ptrs(doc_ptr, errors_ptr)
-        }
+        let mut parser = Parser::new();
+        parser.set_options(options);
+        parser.set_url(url);
+        parser.set_encoding(encoding);
+        parser.parse(r)
     }
 
-    fn handle_result_ptrs(doc_ptr: xmlDocPtr, errors_ptr: *mut c_void) -> Result<Document, Vec<XmlError>> {
-        let errors: Box<Vec<XmlError>> = unsafe { mem::transmute(errors_ptr) };
+    pub(crate) fn handle_result_ptrs(doc_ptr: xmlDocPtr, errors: Vec<XmlError>) -> Result<Document, Vec<XmlError>> {
         match doc_ptr.is_null() {
             true => {
                 unsafe { xmlFreeDoc(doc_ptr) };
@@ -205,24 +306,88 @@ impl Document {
                 // Nokogiri raises the last error, not sure what we want or what would be idiomatic.
                 //Err(xml_get_last_error())
 
-                Err(*errors)
+                Err(errors)
             }
             false => {
                 // TODO: Implement XInclude
-                let doc = _Document{doc_ptr: doc_ptr, errors: *errors, nodes: HashMap::new()};
+                let doc = _Document::new(doc_ptr, errors);
                 Ok(Document(Rc::new(RefCell::new(doc))))
             }
         }
     }
 }
 
+// A `_Document` that no `Rc` handle points to any more may be moved to another thread.
+struct SendDocument(_Document);
+
+unsafe impl Send for SendDocument {}
+
+/// A `Document` that can be shared between threads.
+///
+/// `Document` and `Node` handles are reference counted without synchronization, so they never leave
+/// the thread they were created on. A `SyncDocument` keeps the document behind a `Mutex` and hands out
+/// a temporary `Document` to one thread at a time through `with`.
+#[derive(Clone)]
+pub struct SyncDocument(Arc<Mutex<Option<SendDocument>>>);
+
+impl SyncDocument {
+    /// Runs `f` with exclusive access to the document.
+    ///
+    /// Panics if a `Document` or `Node` handle created inside `f` is still alive when `f` returns;
+    /// the document then stays with that handle and can no longer be used through this `SyncDocument`.
+    pub fn with<F, T>(&self, f: F) -> T where F: FnOnce(&mut Document) -> T {
+        let mut guard = self.0.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let inner = guard.take().expect("Document handles escaped from SyncDocument::with");
+        init_parser();
+        let mut document = Document(Rc::new(RefCell::new(inner.0)));
+        let result = f(&mut document);
+        match Rc::try_unwrap(document.0) {
+            Ok(inner) => *guard = Some(SendDocument(inner.into_inner())),
+            Err(_) => panic!("Document handles escaped from SyncDocument::with"),
+        }
+        result
+    }
+
+    /// Turns this back into a `Document` bound to the current thread.
+    /// Fails if other clones of this `SyncDocument` are still alive.
+    pub fn into_document(self) -> Result<Document, SyncDocument> {
+        let mutex = Arc::try_unwrap(self.0).map_err(SyncDocument)?;
+        let inner = mutex.into_inner().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let inner = inner.expect("Document handles escaped from SyncDocument::with");
+        init_parser();
+        Ok(Document(Rc::new(RefCell::new(inner.0))))
+    }
+}
+
+impl Document {
+    /// Converts the document into a `SyncDocument` that can be sent to and shared between threads.
+    /// Fails, returning the document, if any other `Document` or `Node` handle to it is still alive.
+    pub fn into_sync(self) -> Result<SyncDocument, Document> {
+        let inner = Rc::try_unwrap(self.0).map_err(Document)?;
+        Ok(SyncDocument(Arc::new(Mutex::new(Some(SendDocument(inner.into_inner()))))))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     #[test]
     fn parse_string_test(){
-        assert_eq!(true, Document::parse_string("<root></root>", "", "utf-8", ParseOptions::DEFAULT_XML).is_ok());
-        assert_eq!(true, Document::parse_string("a><root></root>", "", "utf-8", ParseOptions::DEFAULT_XML).is_ok());
+        assert_eq!(true, Document::parse_with_options("<root></root>", "", "utf-8", ParseOptions::DEFAULT_XML).is_ok());
+        assert_eq!(true, Document::parse_with_options("a><root></root>", "", "utf-8", ParseOptions::DEFAULT_XML).is_ok());
+    }
+
+    #[test]
+    fn parse_interior_nul_test(){
+        assert!(Document::parse_with_options("<root>\0</root>", "", "utf-8", ParseOptions::STRICT).is_err());
+        assert!(Document::parse_with_options("<root></root>", "ur\0l", "utf-8", ParseOptions::DEFAULT_XML).is_err());
+    }
+
+    #[test]
+    fn url_test(){
+        let doc = Document::parse_with_options("<root/>", "http://example.com/doc.xml", "utf-8", ParseOptions::DEFAULT_XML).unwrap();
+        assert_eq!(doc.url(), Some(String::from("http://example.com/doc.xml")));
+        assert_eq!(Document::new().unwrap().url(), None);
     }
 
     #[test]
@@ -231,6 +396,39 @@ mod tests {
         let node = doc.get_root_element().unwrap();
     }
 
+    #[test]
+    fn get_element_by_id_test() {
+        let doc = Document::parse(r#"<!DOCTYPE root [<!ATTLIST item key ID #IMPLIED>]>
+            <root><item key="first"/><item xml:id="second"/><item id="third"/></root>"#).unwrap();
+        assert_eq!(doc.get_element_by_id("first").unwrap().get_name(), "item");
+        let second = doc.get_element_by_id("second").unwrap();
+        assert_eq!(second, doc.get_root_element().unwrap().get_child_elements()[1]);
+        // Plain `id` attributes are not IDs without a declaration
+        assert!(doc.get_element_by_id("third").is_none());
+        assert!(doc.get_element_by_id("missing").is_none());
+    }
+
+    #[test]
+    fn raw_document_test() {
+        let doc = unsafe {
+            let c_version = CString::new("1.0").unwrap();
+            Document::from_raw(xmlNewDoc(c_version.as_ptr() as *const u8)).unwrap()
+        };
+        assert!(doc.get_root_element().is_none());
+        assert_eq!(doc.as_ptr(), doc.doc_ptr());
+        assert!(unsafe { Document::from_raw(ptr::null_mut()) }.is_err());
+    }
+
+    #[test]
+    fn deep_clone_test() {
+        let doc = Document::parse("<root><child attribute=\"value\">text</child></root>").unwrap();
+        let copy = doc.deep_clone().unwrap();
+        doc.get_root_element().unwrap().set_name("changed");
+        assert_eq!(copy.get_root_element().unwrap().to_string(false), "<root><child attribute=\"value\">text</child></root>");
+        drop(doc);
+        assert_eq!(copy.get_root_element().unwrap().get_child_elements().len(), 1);
+    }
+
     #[test]
     fn document_can_import_node() {
         let xml_string = String::from(r#"<root>
@@ -244,8 +442,32 @@ mod tests {
 
         let elements = doc1.get_root_element().unwrap().get_child_elements();
         let node = elements.first().unwrap();
-        let imported = doc2.import_node(&mut node.clone()).unwrap();
-        assert!(doc2.get_root_element().unwrap().add_child(imported).is_ok());
+        let mut imported = doc2.import_node(&mut node.clone()).unwrap();
+        assert!(doc2.get_root_element().unwrap().add_child(&mut imported).is_ok());
         assert_eq!(doc2.get_root_element().unwrap().get_child_elements().len(), 3);
     }
+
+    #[test]
+    fn unlinked_nodes_are_freed_without_handles() {
+        let doc = Document::parse("<root><a><b/></a><c/></root>").unwrap();
+        let root = doc.get_root_element().unwrap();
+        let mut a = root.get_first_child().unwrap();
+        let b = a.get_first_child().unwrap();
+        a.unlink();
+        drop(a);
+        // b still refers into the unlinked subtree
+        assert_eq!(doc.0.borrow().unlinked.len(), 1);
+        drop(b);
+        assert!(doc.0.borrow().unlinked.is_empty());
+
+        root.get_first_child().unwrap().unlink();
+        assert!(doc.0.borrow().unlinked.is_empty());
+        assert_eq!(root.to_string(false), "<root/>");
+
+        let mut root = root;
+        root.set_inner_xml("<d/><e/>", ParseOptions::NONET).unwrap();
+        root.set_inner_xml("<f/>", ParseOptions::NONET).unwrap();
+        assert!(doc.0.borrow().unlinked.is_empty());
+        assert_eq!(root.to_string(false), "<root><f/></root>");
+    }
 }
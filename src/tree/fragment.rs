@@ -0,0 +1,116 @@
+use libxml2::xmlNewDocFragment;
+
+use tree::document::Document;
+use tree::node::Node;
+
+/// A lightweight container for nodes of a `Document`, which is never part of the tree itself.
+/// Inserting the fragment moves all of its children to the insertion point in one go and
+/// leaves the fragment empty, so it can be filled again.
+///
+/// ```ignore
+/// let mut fragment = doc.create_fragment()?;
+/// fragment.append_child(&mut first)?;
+/// fragment.append_child(&mut second)?;
+/// list.append_fragment(&mut fragment)?;
+/// ```
+#[derive(Debug, Clone)]
+pub struct DocumentFragment {
+    node: Node,
+}
+
+impl DocumentFragment {
+    /// Creates an empty fragment owned by `document`
+    pub fn new(document: &Document) -> Result<DocumentFragment, ()> {
+        let node_ptr = unsafe { xmlNewDocFragment(document.doc_ptr()) };
+        if node_ptr.is_null() {
+            return Err(());
+        }
+        document.doc_ref().borrow_mut().insert_unlinked(node_ptr);
+        Ok(DocumentFragment { node: Node::wrap(node_ptr, document.doc_ref()) })
+    }
+
+    /// Returns the fragment node itself, of type `NodeType::DocumentFragNode`
+    pub fn as_node(&self) -> &Node {
+        &self.node
+    }
+
+    /// Appends `child` to the fragment, moving it away from its current parent.
+    /// See `Node::add_child`.
+    pub fn append_child(&mut self, child: &mut Node) -> Result<Node, ()> {
+        self.node.add_child(child)
+    }
+
+    /// Returns the nodes currently held by the fragment
+    pub fn get_child_nodes(&self) -> Vec<Node> {
+        self.node.get_child_nodes()
+    }
+
+    /// Returns true iff the fragment holds no nodes
+    pub fn is_empty(&self) -> bool {
+        self.node.get_first_child().is_none()
+    }
+
+    /// Serializes the nodes held by the fragment
+    pub fn to_string(&self, format: bool) -> String {
+        self.get_child_nodes().iter().map(|node| node.to_string(format)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tree::node::NodeType;
+
+    #[test]
+    fn fragment_test() {
+        let doc = Document::parse("<list><item>0</item></list>").unwrap();
+        let mut list = doc.get_root_element().unwrap();
+        let mut fragment = doc.create_fragment().unwrap();
+        assert_eq!(fragment.as_node().get_type(), Some(NodeType::DocumentFragNode));
+        assert!(fragment.is_empty());
+
+        for i in 1..3 {
+            let mut item = Node::new("item", None, doc.doc_ref()).unwrap();
+            item.set_content(&i.to_string());
+            fragment.append_child(&mut item).unwrap();
+        }
+        assert_eq!(fragment.to_string(false), "<item>1</item><item>2</item>");
+
+        let inserted = list.append_fragment(&mut fragment).unwrap();
+        assert_eq!(inserted.len(), 2);
+        assert!(fragment.is_empty());
+        assert_eq!(list.to_string(false), "<list><item>0</item><item>1</item><item>2</item></list>");
+
+        let mut first = list.get_first_child().unwrap();
+        let mut moved = list.get_last_child().unwrap();
+        fragment.append_child(&mut moved).unwrap();
+        first.insert_fragment_before(&mut fragment).unwrap();
+        assert!(fragment.is_empty());
+        assert_eq!(list.to_string(false), "<list><item>2</item><item>0</item><item>1</item></list>");
+
+        let other = Document::new().unwrap();
+        let mut foreign = other.create_fragment().unwrap();
+        assert!(list.append_fragment(&mut foreign).is_err());
+    }
+
+    #[test]
+    fn fragment_insertion_checks_test() {
+        let doc = Document::parse("<root><list>text</list><source>more</source></root>").unwrap();
+        let mut list = doc.get_root_element().unwrap().get_first_child().unwrap();
+        let mut fragment = doc.create_fragment().unwrap();
+        let mut text = list.get_next_sibling().unwrap().get_first_child().unwrap();
+        let mut inner = Node::new("inner", None, doc.doc_ref()).unwrap();
+        fragment.append_child(&mut text).unwrap();
+        fragment.append_child(&mut inner).unwrap();
+
+        // Inserting next to a node of the fragment itself fails before anything is moved
+        assert!(inner.append_fragment(&mut fragment).is_err());
+        assert!(inner.insert_fragment_before(&mut fragment).is_err());
+        assert_eq!(fragment.to_string(false), "more<inner/>");
+
+        let inserted = list.append_fragment(&mut fragment).unwrap();
+        assert!(inserted.iter().all(|node| node.is_valid()));
+        assert_eq!(inserted[0].get_content(), "textmore");
+        assert_eq!(list.to_string(false), "<list>textmore<inner/></list>");
+    }
+}
@@ -1,15 +1,20 @@
 use std::ffi::{ CStr };
-use libxml2::{xmlErrorPtr,
-              xmlResetError};
-use std::os::raw::c_void;
-use std::mem;
+use libxml2::{xmlResetError,
+              xmlInitParser};
+use std::os::raw::{c_char};
+use std::sync::Once;
 
 use std::io::{Read, BufReader};
 use std::fs::File;
 use std::path::Path;
 
+use error::Error;
+
 pub mod document;
 pub mod node;
+pub mod builder;
+pub mod fragment;
+pub mod parser;
 
 
 bitflags! {
@@ -57,8 +62,10 @@ bitflags! {
         const OLD10       = 1 << 17;
         // do not fixup XINCLUDE xml:base uris
         const NOBASEFIX   = 1 << 18;
-        // relax any hardcoded limit from the parser
+        // relax any hardcoded limit from the parser, line numbers are still capped without BIG_LINES
         const HUGE        = 1 << 19;
+        // store line numbers above 65535, see `Node::get_line_number`
+        const BIG_LINES   = 1 << 22;
         // the default options used for parsing XML documents
         const DEFAULT_XML  = Self::RECOVER.bits
             | Self::NONET.bits;
@@ -70,15 +77,35 @@ bitflags! {
     }
 }
 
+impl ParseOptions {
+    /// Strict parsing without network access, entity substitution, DTD loading or relaxed limits.
+    /// This limits what is loaded but not the resources a document takes, for untrusted input
+    /// use it together with `Parser::set_security_policy`.
+    pub fn secure() -> ParseOptions {
+        ParseOptions::NONET
+    }
+}
+
 #[derive(Debug)]
 pub struct XmlError {
-    pub message: String
+    pub message: String,
+    /// Set if the error was not reported by libxml2
+    pub cause: Option<Error>,
+}
+
+impl From<Error> for XmlError {
+    fn from(err: Error) -> XmlError {
+        XmlError { message: err.to_string(), cause: Some(err) }
+    }
 }
 
 pub trait XmlInput {
     const IS_PATH: bool = false;
     fn is_path(&self) -> bool { Self::IS_PATH }
     fn data(&self) -> String;
+    /// Returns a reader for inputs the parser reads as a stream of raw bytes rather than through `data`,
+    /// so the parser detects the encoding itself. Read errors are reported as parse errors.
+    fn reader(&self) -> Option<Box<dyn Read + '_>> { None }
 }
 
 impl XmlInput for str {
@@ -96,33 +123,46 @@ impl XmlInput for String {
 impl XmlInput for Path {
     const IS_PATH: bool = true;
     fn data(&self) -> String {
-        String::from(self.to_str().expect("Could not get path"))
+        self.to_string_lossy().into_owned()
     }
 }
 
-// TODO: Here we could possibly use the file descriptor if detect *nix.
 impl XmlInput for File {
     const IS_PATH: bool = false;
+    /// The contents of the file with invalid UTF-8 replaced and read errors ignored.
+    /// The parser does not use this, it reads the raw bytes through `reader`.
     fn data(&self) -> String {
-        let mut tmp = String::new();
+        let mut tmp = Vec::new();
         {
             let mut a = BufReader::new(self);
-            a.read_to_string(&mut tmp).expect("Could not read_to_string");
+            let _ = a.read_to_end(&mut tmp);
         }
-        tmp
+        String::from_utf8_lossy(&tmp).into_owned()
+    }
+
+    fn reader(&self) -> Option<Box<dyn Read + '_>> {
+        Some(Box::new(self))
     }
 }
 
-extern "C" fn error_vec_pusher(errors_ptr: *mut c_void, libxml_error: xmlErrorPtr) {
-    let mut errors: Box<Vec<XmlError>>;
-    let mut msg: &str;
-    unsafe {
-        msg = CStr::from_ptr((*libxml_error).message).to_str().expect("Failed to get error msg");
-        errors = mem::transmute(errors_ptr);
-    };
-    errors.push(XmlError { message: String::from(msg) });
-
-    // errors will be dropped by Document::handle_result_ptrs
-    mem::forget(errors);
+/// Copies a NUL terminated string owned by libxml2 (empty for a `NULL` pointer)
+pub unsafe fn xml_bytes(ptr: *const u8) -> Vec<u8> {
+    if ptr.is_null() {
+        Vec::new()
+    } else {
+        CStr::from_ptr(ptr as *const c_char).to_bytes().to_vec()
+    }
 }
 
+static INIT_PARSER: Once = Once::new();
+
+/// Initializes libxml2 exactly once, before the first document is created or parsed.
+/// This has to happen before libxml2 is used from more than one thread.
+/// Every thread also registers the callback invalidating the handles of nodes freed by libxml2.
+pub fn init_parser() {
+    INIT_PARSER.call_once(|| unsafe {
+        xmlInitParser();
+        parser::install_entity_loader();
+    });
+    node::install_free_hook();
+}
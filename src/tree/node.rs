@@ -1,11 +1,11 @@
-use std::cell::RefCell;
 use std::rc::Rc;
-use std::ffi::{CStr, CString};
-use std::os::raw::{c_uint};
+use std::cell::{Cell, RefCell};
+use std::ffi::CString;
+use std::os::raw::{c_char, c_int, c_uint, c_void};
 use std::ptr;
-use std::str;
-use std::mem;
-use std::collections::HashMap;
+use std::fmt::Display;
+use std::str::{FromStr, ParseBoolError};
+use std::collections::{HashMap, HashSet};
 
 use libc;
 
@@ -19,30 +19,131 @@ use libxml2::{xmlNodePtr,
               xmlAddPrevSibling,
               xmlGetLastChild,
               xmlUnlinkNode,
-              xmlFreeNode,
               xmlNewDocNode,
+              xmlDocCopyNode,
               xmlGetProp,
               xmlHasProp,
+              xmlHasNsProp,
               xmlSetProp,
-              xmlRemoveProp,
               xmlNodeGetContent,
               xmlNodeAddContentLen,
-              xmlNodeSetName};
+              xmlNodeSetContent,
+              xmlEncodeSpecialChars,
+              xmlNodeSetName,
+              xmlGetLineNo,
+              xmlNodeGetBase,
+              xmlGetNodePath,
+              xmlParseInNodeContext,
+              xmlFreeNodeList,
+              xmlDeregisterNodeDefault,
+              xmlDeregisterNodeFunc,
+              xmlDOMWrapAdoptNode};
 
-use tree::document::DocumentRef;
+use css;
+use error::Error;
+use xpath::{Context, xpath_literal};
+use tree::{ParseOptions, XmlError, xml_bytes};
+use tree::document::{Document, DocumentRef, is_managed};
+use tree::fragment::DocumentFragment;
+use tree::parser::collect_errors;
 
-pub type NodeRef = Rc<RefCell<_Node>>;
+/// A handle to a node inside a `Document`.
+///
+/// All handles to the same node share one `_Node`, which the `_private` field of the `xmlNode` points to.
+/// If libxml2 frees the node, for example when `add_child` merges adjacent text nodes, the handles are
+/// invalidated instead of left dangling, see `is_valid`. Handles keep the document alive as long as they exist.
+#[derive(Debug, Clone)]
+pub struct Node(Rc<_Node>);
 
 #[derive(Debug)]
-pub struct _Node {
-    node_ptr: xmlNodePtr,
-    document: DocumentRef,
-    unlinked: bool,
+struct _Node {
+    // Null once libxml2 has freed the node
+    node_ptr: Cell<xmlNodePtr>,
+    // Replaced when the node moves to another document
+    document: RefCell<DocumentRef>,
 }
 
-#[derive(Debug, Clone)]
-pub struct Node(NodeRef);
+impl Drop for _Node {
+    fn drop(&mut self) {
+        let node_ptr = self.node_ptr.get();
+        if node_ptr.is_null() {
+            return;
+        }
+        unsafe { (*node_ptr)._private = ptr::null_mut() };
+        // The last handle into an unlinked subtree frees it. While the document is borrowed
+        // elsewhere the subtree is left to be freed together with the document.
+        if let Ok(mut document) = self.document.borrow().try_borrow_mut() {
+            document.collect_unlinked(node_ptr);
+        }
+    }
+}
+
+thread_local! {
+    // The callback `node_freed` replaced, `None` until it is installed on this thread
+    static FREE_HOOK: Cell<Option<xmlDeregisterNodeFunc>> = const { Cell::new(None) };
+}
+
+/// Makes libxml2 report the nodes it frees on this thread, so their handles are invalidated.
+/// The callback is per thread in libxml2, `init_parser` calls this on every thread using the crate.
+pub(crate) fn install_free_hook() {
+    FREE_HOOK.with(|hook| if hook.get().is_none() {
+        let previous = unsafe { xmlDeregisterNodeDefault(Some(node_freed)) };
+        // Threads may share the callback, depending on how libxml2 was built
+        hook.set(Some(previous.filter(|&previous| previous as usize != node_freed as unsafe extern "C" fn(xmlNodePtr) as usize)));
+    });
+}
+
+unsafe extern "C" fn node_freed(node_ptr: xmlNodePtr) {
+    let private = (*node_ptr)._private as *const _Node;
+    // Only the `_private` fields of documents owned by a `Document` point to a `_Node`
+    if !private.is_null() && is_managed((*node_ptr).doc) {
+        (*private).node_ptr.set(ptr::null_mut());
+        (*node_ptr)._private = ptr::null_mut();
+    }
+    if let Ok(Some(Some(previous))) = FREE_HOOK.try_with(Cell::get) {
+        previous(node_ptr);
+    }
+}
+
+// The topmost ancestor of a node, the document node for nodes in the tree
+pub(crate) unsafe fn subtree_root(mut node_ptr: xmlNodePtr) -> xmlNodePtr {
+    while !(*node_ptr).parent.is_null() {
+        node_ptr = (*node_ptr).parent;
+    }
+    node_ptr
+}
 
+// Whether a handle refers to the node or one of its descendants or attributes
+pub(crate) unsafe fn has_handles(node_ptr: xmlNodePtr) -> bool {
+    find_in_subtree(node_ptr, |node_ptr| !(*node_ptr)._private.is_null())
+}
+
+// Whether `found` holds for the node or one of its descendants or attributes
+unsafe fn find_in_subtree<F>(node_ptr: xmlNodePtr, mut found: F) -> bool where F: FnMut(xmlNodePtr) -> bool {
+    let mut pending = vec![node_ptr];
+    while let Some(node_ptr) = pending.pop() {
+        if found(node_ptr) {
+            return true;
+        }
+        // The children of an entity reference belong to the entity declaration
+        if (*node_ptr).type_ == 5 {
+            continue;
+        }
+        if (*node_ptr).type_ == 1 {
+            let mut attr_ptr = (*node_ptr).properties;
+            while !attr_ptr.is_null() {
+                pending.push(attr_ptr as xmlNodePtr);
+                attr_ptr = (*attr_ptr).next;
+            }
+        }
+        let mut child_ptr = (*node_ptr).children;
+        while !child_ptr.is_null() {
+            pending.push(child_ptr);
+            child_ptr = (*child_ptr).next;
+        }
+    }
+    false
+}
 
 /// Types of xml nodes
 #[derive(Debug, PartialEq)]
@@ -70,6 +171,17 @@ pub enum NodeType {
     DOCBDocumentNode,
 }
 
+/// How `Node::inner_text` treats whitespace
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Whitespace {
+    /// Keep the text as it is
+    Preserve,
+    /// Remove leading and trailing whitespace
+    Trim,
+    /// Trim and collapse every run of whitespace into a single space
+    Normalize,
+}
+
 impl NodeType {
     /// converts an integer from libxml's `enum NodeType`
     /// to an instance of our `NodeType`
@@ -101,78 +213,84 @@ impl NodeType {
     }
 }
 
-impl Drop for _Node {
-    fn drop(&mut self) {
-        //println!("_Node::drop");
-        if self.unlinked {
-            unsafe { xmlFreeNode(self.node_ptr) }
-        }
-    }
-}
-/*
-impl Drop for Node {
-    fn drop(&mut self) {
-        println!("Node::drop");
-        println!("Before: Strong count: {}, Weak count: {}", Rc::strong_count(&self.0), Rc::weak_count(&self.0));
-
-        {
-            let inner_node = self.0.borrow();
-            println!("unlinked: {}", inner_node.unlinked);
-            if !inner_node.unlinked {
-                return;
-            }
-
-            let mut doc = inner_node.document.try_borrow_mut();
-            if doc.is_err() {
-                return;
-            }
-            let mut inner_doc = doc.unwrap();
-            if !inner_doc.nodes.contains_key(&self.node_ptr()) {
-                return;
-            }
-
-            println!("Removing node {:?} from nodes", &self.node_ptr());
-            inner_doc.nodes.remove(&self.node_ptr());
-        }
-        println!("After: Strong count: {}, Weak count: {}", Rc::strong_count(&self.0), Rc::weak_count(&self.0));
-
-        /*
-        if (Rc::strong_count(&self.0)) <= 1 {
-            xmlFreeNode(&self.node_ptr());
-        }
-        */
-    }
-}
-*/
-
 impl PartialEq for Node {
     /// Two nodes are considered equal, if they point to the same xmlNode.
     fn eq(&self, other: &Node) -> bool {
-        self.node_ptr() == other.node_ptr()
+        Rc::ptr_eq(&self.0, &other.0)
     }
 }
 
 impl Eq for Node {}
 
 impl Node {
+    /// Returns the underlying `xmlNodePtr`, panics if the node has been freed (see `is_valid`)
     pub fn node_ptr(&self) -> xmlNodePtr {
-        self.0.borrow().node_ptr
+        let node_ptr = self.0.node_ptr.get();
+        assert!(!node_ptr.is_null(), "the node has been freed by libxml2");
+        node_ptr
     }
 
     pub fn node_ptr_mut(&mut self) -> xmlNodePtr {
-        self.0.borrow_mut().node_ptr
+        self.node_ptr()
+    }
+
+    // Returns the handle of the node, the node must not be a namespace declaration
+    pub(crate) fn wrap(node_ptr: xmlNodePtr, document: DocumentRef) -> Node {
+        unsafe {
+            let private = (*node_ptr)._private as *const _Node;
+            if !private.is_null() {
+                Rc::increment_strong_count(private);
+                return Node(Rc::from_raw(private));
+            }
+            let node = Rc::new(_Node { node_ptr: Cell::new(node_ptr), document: RefCell::new(document) });
+            (*node_ptr)._private = Rc::as_ptr(&node) as *mut c_void;
+            Node(node)
+        }
+    }
+
+    /// Returns false once libxml2 has freed the node, every other method panics then.
+    /// This happens to the other handles of a text node that `add_child` merges into an adjacent text node.
+    pub fn is_valid(&self) -> bool {
+        !self.0.node_ptr.get().is_null()
+    }
+
+    /// Returns the underlying `xmlNodePtr` for use with `sys`.
+    /// The pointer is valid as long as the node is, see `is_valid`.
+    pub fn as_ptr(&self) -> xmlNodePtr {
+        self.node_ptr()
+    }
+
+    /// Wraps a node of `document` obtained through `sys`.
+    /// A node outside of the tree becomes owned by the document, like one created with `Node::new`.
+    /// Returns `None` for a null pointer, a node of another document or a node other than an element,
+    /// text, CDATA section, comment, processing instruction, entity reference or document fragment.
+    ///
+    /// # Safety
+    ///
+    /// `node_ptr` has to point to a valid node or namespace declaration, which is not freed other than through its `Document`.
+    /// A node outside of the tree must not be owned by anything else, such as a `DocumentFragment` or another unlinked node.
+    /// Its `_private` field has to be null unless it was set by a `Node` handle.
+    pub unsafe fn from_raw(node_ptr: xmlNodePtr, document: &Document) -> Option<Node> {
+        if node_ptr.is_null() || !matches!((*node_ptr).type_, 1 | 3 | 4 | 5 | 7 | 8 | 11) || (*node_ptr).doc != document.doc_ptr() {
+            return None;
+        }
+        if (*node_ptr).parent.is_null() {
+            document.doc_ref().borrow_mut().insert_unlinked(node_ptr);
+        }
+        Some(Node::wrap(node_ptr, document.doc_ref()))
     }
 
-    pub fn wrap(node_ptr: xmlNodePtr, document: DocumentRef) -> Node {
-        let node = _Node { node_ptr, document, unlinked: false };
-        Node(Rc::new(RefCell::new(node)))
+    /// Returns a reference to the document this node is bound to
+    pub fn doc_ref(&self) -> DocumentRef {
+        self.0.document.borrow().clone()
     }
 
     /// Create a new node, bound to a given document.
+    /// The node is owned by the document until it is inserted into the tree.
     pub fn new(name: &str, ns: Option<xmlNsPtr>, document: DocumentRef) -> Result<Self, ()> {
         // We will only allow to work with document-bound nodes for now, to avoid the problems of memory management.
 
-        let c_name = CString::new(name).unwrap();
+        let c_name = CString::new(name).map_err(|_| ())?;
         let ns_ptr = match ns {
             None => ptr::null_mut(),
             Some(ns) => ns,
@@ -181,12 +299,30 @@ impl Node {
         if node_ptr.is_null() {
             Err(())
         } else {
-            let new_node = Node::wrap(node_ptr, document.clone());
-            document.borrow_mut().insert_node(node_ptr, new_node.clone());
-            Ok(new_node)
+            document.borrow_mut().insert_unlinked(node_ptr);
+            Ok(Node::wrap(node_ptr, document))
         }
     }
 
+    /// Returns true iff both nodes are bound to the same document
+    pub fn same_document(&self, other: &Node) -> bool {
+        Rc::ptr_eq(&self.doc_ref(), &other.doc_ref())
+    }
+
+    /// Copies the node within its document.
+    /// A `deep` copy includes all descendants, otherwise only the node with its attributes and namespaces is copied.
+    /// The copy is not part of the tree until it is inserted somewhere.
+    pub fn clone_subtree(&self, deep: bool) -> Result<Node, ()> {
+        let recursive = if deep { 1 } else { 2 };
+        let doc_ptr = self.doc_ref().borrow().doc_ptr;
+        let node_ptr = unsafe { xmlDocCopyNode(self.node_ptr(), doc_ptr, recursive) };
+        if node_ptr.is_null() {
+            return Err(());
+        }
+        self.doc_ref().borrow_mut().insert_unlinked(node_ptr);
+        Node::ptr_as_result(self, node_ptr)
+    }
+
     /// Returns all child nodes of the given node as a vector
     pub fn get_child_nodes(&self) -> Vec<Node> {
         let mut nodes = Vec::new();
@@ -208,6 +344,73 @@ impl Node {
         ).collect::<Vec<Node>>()
     }
 
+    /// Returns all descendant elements named `name` in document order, `"*"` matches every element.
+    /// The name is compared with the qualified name, including the prefix if there is one.
+    pub fn get_elements_by_tag_name(&self, name: &str) -> Vec<Node> {
+        let mut elements = Vec::new();
+        self.collect_descendant_elements(&|element| name == "*" || element.get_qualified_name() == name, &mut elements);
+        elements
+    }
+
+    /// Returns all descendant elements with the local name `local` in the namespace `uri` in document order.
+    /// An empty `uri` matches elements without a namespace, `"*"` matches any namespace or local name.
+    pub fn get_elements_by_tag_name_ns(&self, uri: &str, local: &str) -> Vec<Node> {
+        let mut elements = Vec::new();
+        self.collect_descendant_elements(&|element| {
+            let uri_matches = match element.get_namespace_uri() {
+                _ if uri == "*" => true,
+                Some(ref element_uri) => element_uri == uri,
+                None => uri.is_empty(),
+            };
+            uri_matches && (local == "*" || element.get_name() == local)
+        }, &mut elements);
+        elements
+    }
+
+    /// Returns the descendant elements matching the CSS selector `selector` in document order,
+    /// such as `div.item > a[href^='http']`. See the `css` module for the supported selectors.
+    /// The selector is matched below this node only, so it cannot refer to ancestors of this node.
+    pub fn select(&self, selector: &str) -> Result<Vec<Node>, Error> {
+        let xpath = css::to_relative_xpath(selector)?;
+        let invalid = || Error::InvalidSelector(format!("'{}' compiled to the invalid XPath expression '{}'", selector, xpath));
+        let context = Context::from_doc_ref(self.doc_ref()).map_err(|_| invalid())?;
+        let result = context.node_evaluate(&xpath, self).map_err(|_| invalid())?;
+        Ok(result.get_nodes_as_vec())
+    }
+
+    fn is_descendant_of(&self, ancestor: &Node) -> bool {
+        let ancestor_ptr = ancestor.node_ptr();
+        let mut current_ptr = unsafe { (*self.node_ptr()).parent };
+        while !current_ptr.is_null() {
+            if current_ptr == ancestor_ptr {
+                return true;
+            }
+            current_ptr = unsafe { (*current_ptr).parent };
+        }
+        false
+    }
+
+    fn collect_descendant_elements<F>(&self, filter: &F, elements: &mut Vec<Node>) where F: Fn(&Node) -> bool {
+        for child in self.get_child_elements() {
+            if filter(&child) {
+                elements.push(child.clone());
+            }
+            child.collect_descendant_elements(filter, elements);
+        }
+    }
+
+    fn get_qualified_name(&self) -> String {
+        let name = self.get_name();
+        unsafe {
+            let ns_ptr = (*self.node_ptr()).ns;
+            if ns_ptr.is_null() || (*ns_ptr).prefix.is_null() {
+                name
+            } else {
+                format!("{}:{}", String::from_utf8_lossy(&xml_bytes((*ns_ptr).prefix)), name)
+            }
+        }
+    }
+
     /// Get the node type
     pub fn get_type(&self) -> Option<NodeType> {
         NodeType::from_c_int(unsafe { (*self.node_ptr()).type_ })
@@ -234,118 +437,613 @@ impl Node {
 
     /// Returns the next sibling if it exists
     pub fn get_next_sibling(&self) -> Option<Node> {
-        let node_ptr = self.0.borrow().node_ptr;
-        let next_sibling_ptr = unsafe { (*node_ptr).next };
+        let next_sibling_ptr = unsafe { (*self.node_ptr()).next };
         Node::ptr_as_option(self, next_sibling_ptr)
     }
 
-    /// Creates a new `Node` as child to the self `Node`
+    /// Returns the parent if it exists.
+    /// The parent of the root element is the document node, the parent of an attribute is its element.
+    pub fn get_parent(&self) -> Option<Node> {
+        let parent_ptr = unsafe { (*self.node_ptr()).parent };
+        Node::ptr_as_option(self, parent_ptr)
+    }
+
+    /// Returns the namespace URI of an element or attribute, if it is in a namespace
+    pub fn get_namespace_uri(&self) -> Option<String> {
+        unsafe {
+            let ns_ptr = (*self.node_ptr()).ns;
+            if ns_ptr.is_null() || (*ns_ptr).href.is_null() {
+                None
+            } else {
+                Some(String::from_utf8_lossy(&xml_bytes((*ns_ptr).href)).into_owned())
+            }
+        }
+    }
+
+    /// Takes this node, its descendants and their attributes out of their namespaces, so they are matched by their local name.
+    /// The namespace declarations stay in place, nodes inside entity references keep their namespaces.
+    pub fn recursively_remove_namespaces(&self) {
+        unsafe {
+            let mut pending = vec![self.node_ptr()];
+            while let Some(node_ptr) = pending.pop() {
+                // Only elements, documents and fragments contain nodes of their own
+                match (*node_ptr).type_ {
+                    1 => {
+                        (*node_ptr).ns = ptr::null_mut();
+                        let mut attr_ptr = (*node_ptr).properties;
+                        while !attr_ptr.is_null() {
+                            (*attr_ptr).ns = ptr::null_mut();
+                            attr_ptr = (*attr_ptr).next;
+                        }
+                    },
+                    2 => {
+                        (*node_ptr).ns = ptr::null_mut();
+                        continue;
+                    },
+                    9 | 11 | 13 => {},
+                    _ => continue,
+                }
+                let mut child_ptr = (*node_ptr).children;
+                while !child_ptr.is_null() {
+                    pending.push(child_ptr);
+                    child_ptr = (*child_ptr).next;
+                }
+            }
+        }
+    }
+
+    /// Returns an XPath expression locating this node, such as `/root/child[2]/@attr`
+    pub fn get_path(&self) -> Option<String> {
+        unsafe {
+            let path_ptr = xmlGetNodePath(self.node_ptr());
+            if path_ptr.is_null() {
+                return None;
+            }
+            let path = String::from_utf8_lossy(&xml_bytes(path_ptr)).into_owned();
+            libc::free(path_ptr as *mut libc::c_void);
+            Some(path)
+        }
+    }
+
+    /// Like `get_path`, but names in a namespace use the prefixes of `namespaces` (prefix to URI),
+    /// so the path selects this node again when evaluated in an `xpath::Context` with the same namespaces registered.
+    /// Names in a namespace without a prefix in `namespaces` are matched by `local-name()` and `namespace-uri()`.
+    pub fn get_path_with_namespaces(&self, namespaces: &HashMap<String, String>) -> String {
+        let mut steps = Vec::new();
+        let mut current = Some(self.clone());
+        while let Some(node) = current {
+            match node.get_type() {
+                Some(NodeType::DocumentNode) | Some(NodeType::HtmlDocumentNode) | None => break,
+                _ => steps.push(node.get_path_step(namespaces)),
+            }
+            current = node.get_parent();
+        }
+        steps.reverse();
+        format!("/{}", steps.join("/"))
+    }
+
+    fn get_path_step(&self, namespaces: &HashMap<String, String>) -> String {
+        let node_type = self.get_type();
+        match node_type {
+            Some(NodeType::ElementNode) => {
+                let name = self.get_name();
+                let uri = self.get_namespace_uri();
+                let position = self.get_path_position(|n| {
+                    n.get_type() == node_type && n.get_name() == name && n.get_namespace_uri() == uri
+                });
+                format!("{}{}", self.get_path_name_test(namespaces), position)
+            },
+            Some(NodeType::AttributeNode) => format!("@{}", self.get_path_name_test(namespaces)),
+            Some(NodeType::TextNode) | Some(NodeType::CDataSectionNode) => {
+                let position = self.get_path_position(|n| n.is_text_node() || n.get_type() == Some(NodeType::CDataSectionNode));
+                format!("text(){}", position)
+            },
+            Some(NodeType::CommentNode) => {
+                format!("comment(){}", self.get_path_position(|n| n.get_type() == node_type))
+            },
+            Some(NodeType::PiNode) => {
+                let name = self.get_name();
+                let position = self.get_path_position(|n| n.get_type() == node_type && n.get_name() == name);
+                format!("processing-instruction({}){}", xpath_literal(&name), position)
+            },
+            _ => format!("node(){}", self.get_path_position(|_| true)),
+        }
+    }
+
+    fn get_path_name_test(&self, namespaces: &HashMap<String, String>) -> String {
+        let name = self.get_name();
+        match self.get_namespace_uri() {
+            None => name,
+            Some(uri) => match namespaces.iter().filter(|&(_, href)| *href == uri).map(|(prefix, _)| prefix).min() {
+                Some(prefix) => format!("{}:{}", prefix, name),
+                None => format!("*[local-name()={} and namespace-uri()={}]", xpath_literal(&name), xpath_literal(&uri)),
+            },
+        }
+    }
+
+    // The position predicate among the siblings matching `same_kind`, empty if there is only one such sibling
+    fn get_path_position<F>(&self, same_kind: F) -> String where F: Fn(&Node) -> bool {
+        let siblings = match self.get_parent() {
+            Some(parent) => parent.get_child_nodes(),
+            None => return String::new(),
+        };
+        let matching: Vec<&Node> = siblings.iter().filter(|n| same_kind(n)).collect();
+        if matching.len() < 2 {
+            return String::new();
+        }
+        match matching.iter().position(|n| *n == self) {
+            Some(index) => format!("[{}]", index + 1),
+            None => String::new(),
+        }
+    }
+
+    /// Appends `child` as the last child of the self `Node`, moving it away from its current parent.
+    /// A `child` of another document is moved into this document, see `adopt`.
+    /// Fails if `child` is the self `Node` or one of its ancestors.
+    /// A text node may be merged into an adjacent text node and freed, `child` is then updated to point to
+    /// the merged node while other handles to it become invalid. An attribute replaces the attribute of the same name,
+    /// which stays owned by the document.
     pub fn add_child(&mut self, child: &mut Node) -> Result<Node, ()> {
-        let node_ptr = unsafe { xmlAddChild(self.node_ptr(), child.node_ptr()) };
+        if *self == *child || self.is_descendant_of(child) {
+            return Err(());
+        }
+        if !self.same_document(child) {
+            self.adopt(child, self.node_ptr())?;
+        }
+        let document = self.doc_ref();
+        let child_ptr = child.node_ptr();
+        Node::unlink_replaced_attribute(&document, self.node_ptr(), child_ptr);
+        Node::unlink_ptr(&document, child_ptr);
+        let node_ptr = unsafe { xmlAddChild(self.node_ptr(), child_ptr) };
+        if node_ptr.is_null() {
+            // libxml2 did not take the child, so it stays with the document
+            return Err(());
+        }
+        document.borrow_mut().remove_unlinked(child_ptr);
+        if node_ptr != child_ptr {
+            *child = Node::wrap(node_ptr, document);
+        }
         Node::ptr_as_result(self, node_ptr)
     }
 
     /// Add a previous sibling
+    /// A `new_sibling` of another document is moved into this document if the self `Node` has a parent, see `adopt`.
+    /// Nothing happens if `new_sibling` is the self `Node` or one of its ancestors.
+    /// Text nodes may be merged like with `add_child`, the handles to a merged `new_sibling` become invalid.
     pub fn add_prev_sibling(&mut self, new_sibling: Node) -> Option<Node> {
         // TODO: Think of using a Result type, the libxml2 call returns NULL on error, or the child node on success
+        if *self == new_sibling || self.is_descendant_of(&new_sibling) {
+            return None;
+        }
+        if !self.same_document(&new_sibling) {
+            let parent_ptr = unsafe { (*self.node_ptr()).parent };
+            self.adopt(&new_sibling, parent_ptr).ok()?;
+        }
+        let document = self.doc_ref();
+        let sibling_ptr = new_sibling.node_ptr();
+        unsafe { Node::unlink_replaced_attribute(&document, (*self.node_ptr()).parent, sibling_ptr) };
+        Node::unlink_ptr(&document, sibling_ptr);
+        let node_ptr = unsafe { xmlAddPrevSibling(self.node_ptr(), sibling_ptr) };
+        if node_ptr.is_null() {
+            None
+        } else {
+            document.borrow_mut().remove_unlinked(sibling_ptr);
+            Node::ptr_as_option(self, node_ptr)
+        }
+    }
+
+    // Moves `node` with its descendants from its document into the document of the self `Node`
+    // with `xmlDOMWrapAdoptNode`, to be inserted below `parent_ptr`. The namespaces the nodes use are
+    // looked up from `parent_ptr` or declared anew, strings and IDs are moved over to the new document.
+    // The handles to the nodes are bound to the new document. Fails without a parent to insert below,
+    // as the namespaces would be left behind in the old document.
+    fn adopt(&self, node: &Node, parent_ptr: xmlNodePtr) -> Result<(), ()> {
+        match node.get_type() {
+            Some(NodeType::ElementNode) | Some(NodeType::AttributeNode) | Some(NodeType::TextNode) |
+            Some(NodeType::CDataSectionNode) | Some(NodeType::EntityRefNode) | Some(NodeType::PiNode) |
+            Some(NodeType::CommentNode) if !parent_ptr.is_null() => {},
+            _ => return Err(()),
+        }
+        let source = node.doc_ref();
+        let destination = self.doc_ref();
+        let node_ptr = node.node_ptr();
+        Node::unlink_ptr(&source, node_ptr);
+        source.borrow_mut().remove_unlinked(node_ptr);
+        let source_ptr = source.borrow().doc_ptr;
+        let destination_ptr = destination.borrow().doc_ptr;
+        if unsafe { xmlDOMWrapAdoptNode(ptr::null_mut(), source_ptr, node_ptr, destination_ptr, parent_ptr, 0) } != 0 {
+            source.borrow_mut().insert_unlinked(node_ptr);
+            return Err(());
+        }
+        destination.borrow_mut().insert_unlinked(node_ptr);
         unsafe {
-            if xmlAddPrevSibling(self.node_ptr(), new_sibling.node_ptr()).is_null() {
-                None
-            } else {
-                Some(new_sibling)
+            find_in_subtree(node_ptr, |node_ptr| {
+                let private = (*node_ptr)._private as *const _Node;
+                if !private.is_null() {
+                    *(*private).document.borrow_mut() = destination.clone();
+                }
+                false
+            });
+        }
+        Ok(())
+    }
+
+    // Unlinks the node, which is owned by the document afterwards. Frees the node as well as
+    // the subtree it was taken out of, if no handle refers to them any more.
+    fn unlink_ptr(document: &DocumentRef, node_ptr: xmlNodePtr) {
+        let mut document = document.borrow_mut();
+        unsafe {
+            let parent_ptr = (*node_ptr).parent;
+            xmlUnlinkNode(node_ptr);
+            document.insert_unlinked(node_ptr);
+            if !parent_ptr.is_null() {
+                document.collect_unlinked(parent_ptr);
             }
         }
+        document.collect_unlinked(node_ptr);
+    }
+
+    // libxml2 frees the attribute of `element_ptr` which `attr_ptr` replaces, unlink it instead
+    fn unlink_replaced_attribute(document: &DocumentRef, element_ptr: xmlNodePtr, attr_ptr: xmlNodePtr) {
+        unsafe {
+            if element_ptr.is_null() || (*element_ptr).type_ != 1 || (*attr_ptr).type_ != 2 {
+                return;
+            }
+            let ns_ptr = (*attr_ptr).ns;
+            let href = if ns_ptr.is_null() { ptr::null() } else { (*ns_ptr).href };
+            let replaced_ptr = xmlHasNsProp(element_ptr, (*attr_ptr).name, href) as xmlNodePtr;
+            // xmlHasNsProp may also find the default value declared in the DTD
+            if !replaced_ptr.is_null() && replaced_ptr != attr_ptr && (*replaced_ptr).type_ == 2 {
+                Node::unlink_ptr(document, replaced_ptr);
+            }
+        }
+    }
+
+    /// Moves all nodes of `fragment` to the end of the children of the self `Node`, leaving the fragment empty.
+    /// Returns the inserted nodes, text nodes may have been merged like with `add_child`.
+    /// Fails without moving any node for a fragment of another document or if the self `Node` is inside the fragment.
+    pub fn append_fragment(&mut self, fragment: &mut DocumentFragment) -> Result<Vec<Node>, ()> {
+        if !self.accepts_fragment(fragment) {
+            return Err(());
+        }
+        let mut inserted = Vec::new();
+        for mut child in fragment.get_child_nodes() {
+            inserted.push(self.add_child(&mut child)?);
+        }
+        Ok(inserted)
+    }
+
+    /// Moves all nodes of `fragment` in front of the self `Node`, leaving the fragment empty.
+    /// Returns the inserted nodes, text nodes may have been merged like with `add_prev_sibling`.
+    /// Fails without moving any node like `append_fragment`, or if the self `Node` cannot have siblings.
+    pub fn insert_fragment_before(&mut self, fragment: &mut DocumentFragment) -> Result<Vec<Node>, ()> {
+        match self.get_type() {
+            Some(NodeType::ElementNode) | Some(NodeType::TextNode) | Some(NodeType::CDataSectionNode) |
+            Some(NodeType::EntityRefNode) | Some(NodeType::PiNode) | Some(NodeType::CommentNode) => {},
+            _ => return Err(()),
+        }
+        if self.get_parent().is_none() || !self.accepts_fragment(fragment) {
+            return Err(());
+        }
+        let mut inserted = Vec::new();
+        for child in fragment.get_child_nodes() {
+            inserted.push(self.add_prev_sibling(child).ok_or(())?);
+        }
+        Ok(inserted)
+    }
+
+    // Whether the nodes of `fragment` can be inserted here, checked before the first one is moved
+    fn accepts_fragment(&self, fragment: &DocumentFragment) -> bool {
+        let fragment_node = fragment.as_node();
+        self.same_document(fragment_node) && *self != *fragment_node && !self.is_descendant_of(fragment_node)
     }
 
     /// Unbinds the Node from its siblings and Parent, but not from the Document it belongs to.
-    /// If the node is not inserted into the DOM afterwards, it will be lost after the program terminates.
-    /// From a low level view, the unbound node is stripped from the context it is and inserted into a (hidden) document-fragment.
+    /// The Document keeps ownership of the unbound node, so it can be inserted into the DOM again;
+    /// otherwise it is freed once no handle refers to it or its descendants any more.
+    /// Nothing happens for the document node, fragments, the DTD and declarations.
     pub fn unlink(&mut self) {
-        let node_type = self.get_type();
-        if node_type != Some(NodeType::DocumentNode) && node_type != Some(NodeType::DocumentFragNode) && !self.0.borrow().unlinked {
+        match self.get_type() {
+            Some(NodeType::ElementNode) | Some(NodeType::AttributeNode) | Some(NodeType::TextNode) |
+            Some(NodeType::CDataSectionNode) | Some(NodeType::EntityRefNode) | Some(NodeType::PiNode) |
+            Some(NodeType::CommentNode) => Node::unlink_ptr(&self.doc_ref(), self.node_ptr()),
+            _ => {},
+        }
+    }
+
+    /// Parses `xml` as well-balanced content in the context of this node, so the namespace prefixes
+    /// in scope here can be used. The parsed nodes are owned by the document until they are inserted into the tree.
+    pub fn parse_fragment(&self, xml: &str, options: ParseOptions) -> Result<Vec<Node>, Vec<XmlError>> {
+        if xml.is_empty() {
+            return Ok(Vec::new());
+        }
+        let mut list_ptr: xmlNodePtr = ptr::null_mut();
+        let (result, mut errors) = collect_errors(|| unsafe {
+            xmlParseInNodeContext(self.node_ptr(), xml.as_ptr() as *const c_char, xml.len() as c_int, options.bits, &mut list_ptr)
+        });
+        if result != 0 {
+            if !list_ptr.is_null() {
+                unsafe { xmlFreeNodeList(list_ptr) };
+            }
+            if errors.is_empty() {
+                errors.push(XmlError { message: String::from("Could not parse fragment"), cause: None });
+            }
+            return Err(errors);
+        }
+        let mut nodes = Vec::new();
+        while !list_ptr.is_null() {
             unsafe {
-                self.0.borrow_mut().unlinked = true;
-                let doc = &self.0.borrow().document;
-                doc.borrow_mut().nodes.remove(&self.node_ptr());
-                xmlUnlinkNode( self.node_ptr() );
+                // Split the list, so every node can be inserted on its own
+                let next_ptr = (*list_ptr).next;
+                (*list_ptr).next = ptr::null_mut();
+                (*list_ptr).prev = ptr::null_mut();
+                self.doc_ref().borrow_mut().insert_unlinked(list_ptr);
+                nodes.push(Node::wrap(list_ptr, self.doc_ref()));
+                list_ptr = next_ptr;
+            }
+        }
+        Ok(nodes)
+    }
+
+    /// Replaces the children of this node with the nodes parsed from `xml` with `options`, see `parse_fragment`.
+    /// The children stay untouched if `xml` is not well-balanced or the node is not an element or document.
+    pub fn set_inner_xml(&mut self, xml: &str, options: ParseOptions) -> Result<(), Vec<XmlError>> {
+        let nodes = self.parse_fragment(xml, options)?;
+        // Checked before the children are unlinked: an element or document takes any parsed node,
+        // which are unlinked nodes of this document, so none of the insertions below fails
+        match self.get_type() {
+            Some(NodeType::ElementNode) | Some(NodeType::DocumentNode) => {},
+            _ => return Err(vec![XmlError { message: String::from("Only elements and documents have inner XML"), cause: None }]),
+        }
+        for mut child in self.get_child_nodes() {
+            child.unlink();
+        }
+        for mut node in nodes {
+            self.add_child(&mut node)
+                .map_err(|_| vec![XmlError { message: String::from("Could not insert fragment"), cause: None }])?;
+        }
+        Ok(())
+    }
+
+    /// Returns the line in the source document the node was parsed from, `None` for nodes that were not parsed.
+    /// Nodes after line 65535 report 65535 unless the document was parsed with `ParseOptions::BIG_LINES`,
+    /// which `ParseOptions::HUGE` does not include.
+    pub fn get_line_number(&self) -> Option<usize> {
+        let line = unsafe { xmlGetLineNo(self.node_ptr()) };
+        if line > 0 {
+            Some(line as usize)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the base URI of the node, taking `xml:base` attributes and the document URL into account
+    pub fn base_uri(&self) -> Option<String> {
+        let doc_ptr = self.doc_ref().borrow().doc_ptr;
+        unsafe {
+            let base_ptr = xmlNodeGetBase(doc_ptr, self.node_ptr());
+            if base_ptr.is_null() {
+                return None;
             }
+            let base = String::from_utf8_lossy(&xml_bytes(base_ptr)).into_owned();
+            libc::free(base_ptr as *mut libc::c_void);
+            Some(base)
         }
     }
 
     /// Returns the content of the node
-    /// (empty string if content pointer is `NULL`)
+    /// (empty string if content pointer is `NULL`, invalid UTF-8 is replaced)
     pub fn get_content(&self) -> String {
-        let content_ptr = unsafe { xmlNodeGetContent(self.node_ptr()) };
-        if content_ptr.is_null() {
-            return String::new();
-        }  //empty string
-        let c_string = unsafe { CStr::from_ptr(content_ptr as *const i8) };
-        str::from_utf8(c_string.to_bytes()).unwrap().to_owned()
+        String::from_utf8_lossy(&self.content_bytes()).into_owned()
+    }
+
+    /// Returns the content of the node, failing on invalid UTF-8
+    pub fn try_get_content(&self) -> Result<String, Error> {
+        Ok(String::from_utf8(self.content_bytes())?)
+    }
+
+    fn content_bytes(&self) -> Vec<u8> {
+        unsafe {
+            let content_ptr = xmlNodeGetContent(self.node_ptr());
+            let bytes = xml_bytes(content_ptr);
+            libc::free(content_ptr as *mut libc::c_void);
+            bytes
+        }
+    }
+
+    /// Returns the text of this node and all its descendants,
+    /// leaving out comments and processing instructions
+    pub fn get_text_content(&self) -> String {
+        let mut text = String::new();
+        self.collect_text(true, &mut text);
+        text
+    }
+
+    /// Returns the text of the direct children of this node only,
+    /// leaving out comments, processing instructions and the text of child elements
+    pub fn get_child_text(&self) -> String {
+        let mut text = String::new();
+        self.collect_text(false, &mut text);
+        text
+    }
+
+    fn collect_text(&self, recursive: bool, text: &mut String) {
+        match self.get_type() {
+            Some(NodeType::TextNode) | Some(NodeType::CDataSectionNode) | Some(NodeType::EntityRefNode) => {
+                text.push_str(&self.get_content())
+            },
+            Some(NodeType::CommentNode) | Some(NodeType::PiNode) => {},
+            _ => for child in self.get_child_nodes() {
+                match child.get_type() {
+                    Some(NodeType::ElementNode) if !recursive => {},
+                    _ => child.collect_text(recursive, text),
+                }
+            },
+        }
+    }
+
+    /// Replaces the content of this `Node` with `content`.
+    /// For elements and attributes special characters like `&` and `<` are escaped, so `content` ends up as literal text.
+    /// The content is left unchanged if it contains a NUL byte or the node is read-only, see `try_set_content`.
+    pub fn set_content(&mut self, content: &str) {
+        let _ = self.try_set_content(content);
+    }
+
+    /// Replaces the content of this `Node` with `content`, failing if it contains a NUL byte.
+    /// Fails with `Error::ReadOnlyNode` for entity references, the document node, the DTD and declarations.
+    pub fn try_set_content(&mut self, content: &str) -> Result<(), Error> {
+        let c_content = CString::new(content)?;
+        match self.get_type() {
+            Some(NodeType::ElementNode) | Some(NodeType::AttributeNode) | Some(NodeType::DocumentFragNode) => unsafe {
+                let doc_ptr = self.doc_ref().borrow().doc_ptr;
+                let encoded_ptr = xmlEncodeSpecialChars(doc_ptr, c_content.as_ptr() as *const u8);
+                if encoded_ptr.is_null() {
+                    return Ok(());
+                }
+                let result = self.set_content_ptr(encoded_ptr);
+                libc::free(encoded_ptr as *mut libc::c_void);
+                result
+            },
+            // These store their content verbatim, there is nothing to escape
+            _ => self.set_content_ptr(c_content.as_ptr() as *const u8),
+        }
+    }
+
+    /// Replaces the content of this `Node` with already escaped markup, such as `&amp;` or `&copy;`.
+    /// Entity references are kept as references. Nothing happens if `content` contains a NUL byte
+    /// or the node is read-only, see `try_set_raw_content`.
+    pub fn set_raw_content(&mut self, content: &str) {
+        let _ = self.try_set_raw_content(content);
+    }
+
+    /// Replaces the content of this `Node` with already escaped markup, failing if it contains a NUL byte.
+    /// Fails with `Error::ReadOnlyNode` like `try_set_content`.
+    pub fn try_set_raw_content(&mut self, content: &str) -> Result<(), Error> {
+        let c_content = CString::new(content)?;
+        self.set_content_ptr(c_content.as_ptr() as *const u8)
+    }
+
+    fn set_content_ptr(&mut self, content_ptr: *const u8) -> Result<(), Error> {
+        match self.get_type() {
+            Some(NodeType::ElementNode) | Some(NodeType::AttributeNode) | Some(NodeType::DocumentFragNode) => {
+                // libxml2 frees the current children, unlink those handles refer to so the handles stay valid
+                let document = self.doc_ref();
+                unsafe {
+                    let mut child_ptr = (*self.node_ptr()).children;
+                    while !child_ptr.is_null() {
+                        let next_ptr = (*child_ptr).next;
+                        if has_handles(child_ptr) {
+                            Node::unlink_ptr(&document, child_ptr);
+                        }
+                        child_ptr = next_ptr;
+                    }
+                }
+            },
+            Some(NodeType::TextNode) | Some(NodeType::CDataSectionNode) | Some(NodeType::PiNode) | Some(NodeType::CommentNode) => {},
+            // libxml2 would free the entity declaration an entity reference points to along with its content
+            _ => return Err(Error::ReadOnlyNode),
+        }
+        unsafe { xmlNodeSetContent(self.node_ptr(), content_ptr) };
+        Ok(())
     }
 
     /// Append text to this `Node`
+    /// Nothing is appended if `content` contains a NUL byte, see `try_append_text`.
     pub fn append_text(&mut self, content: &str) {
+        let _ = self.try_append_text(content);
+    }
+
+    /// Append text to this `Node`, failing if `content` contains a NUL byte
+    pub fn try_append_text(&mut self, content: &str) -> Result<(), Error> {
         let c_len = content.len() as i32;
         if c_len > 0 {
-            let c_content = CString::new(content).unwrap();
+            let c_content = CString::new(content)?;
             unsafe {
                 xmlNodeAddContentLen(self.node_ptr(), c_content.as_ptr() as *const u8, c_len);
             }
         }
+        Ok(())
     }
 
-    /// Returns the name of the node (empty string if name pointer is `NULL`)
+    /// Returns the name of the node (empty string if name pointer is `NULL`, invalid UTF-8 is replaced)
     pub fn get_name(&self) -> String {
-        let name_ptr = unsafe { (*self.node_ptr()).name as *const i8 };
-        if name_ptr.is_null() {
-            return String::new();
-        }  //empty string
-        let c_string = unsafe { CStr::from_ptr(name_ptr) };
-        str::from_utf8(c_string.to_bytes()).unwrap().to_owned()
+        let name = unsafe { xml_bytes((*self.node_ptr()).name) };
+        String::from_utf8_lossy(&name).into_owned()
+    }
+
+    /// Returns the name of the node, failing on invalid UTF-8
+    pub fn try_get_name(&self) -> Result<String, Error> {
+        let name = unsafe { xml_bytes((*self.node_ptr()).name) };
+        Ok(String::from_utf8(name)?)
     }
 
     /// Sets the name of this `Node`
+    /// The name is left unchanged if `name` contains a NUL byte, see `try_set_name`.
     pub fn set_name(&mut self, name: &str) {
-        let c_name = CString::new(name).unwrap();
+        let _ = self.try_set_name(name);
+    }
+
+    /// Sets the name of this `Node`, failing if `name` contains a NUL byte
+    pub fn try_set_name(&mut self, name: &str) -> Result<(), Error> {
+        let c_name = CString::new(name)?;
         unsafe { xmlNodeSetName(self.node_ptr_mut(), c_name.as_ptr() as *const u8) }
+        Ok(())
     }
 
-    /// Get a copy of the attributes of this node
+    /// Get a copy of the attributes of this node (invalid UTF-8 is replaced)
     pub fn get_properties(&self) -> HashMap<String, String> {
+        self.properties_bytes().into_iter().map(|(name, value)| {
+            (String::from_utf8_lossy(&name).into_owned(), String::from_utf8_lossy(&value).into_owned())
+        }).collect()
+    }
+
+    /// Get a copy of the attributes of this node, failing on invalid UTF-8
+    pub fn try_get_properties(&self) -> Result<HashMap<String, String>, Error> {
         let mut attributes = HashMap::new();
-        let mut attr_names = Vec::new();
+        for (name, value) in self.properties_bytes() {
+            attributes.insert(String::from_utf8(name)?, String::from_utf8(value)?);
+        }
+        Ok(attributes)
+    }
+
+    fn properties_bytes(&self) -> Vec<(Vec<u8>, Vec<u8>)> {
+        let mut attributes = Vec::new();
         unsafe {
             let mut current_prop = (*self.node_ptr()).properties;
             while !current_prop.is_null() {
-                let name_ptr = (*current_prop).name;
-                let c_name_string = CStr::from_ptr(name_ptr as *const i8);
-                let name = str::from_utf8(c_name_string.to_bytes()).unwrap().to_owned();
-                attr_names.push(name);
+                let name = xml_bytes((*current_prop).name);
+                let value = Node::wrap(current_prop as xmlNodePtr, self.doc_ref()).content_bytes();
+                attributes.push((name, value));
                 current_prop = (*current_prop).next;
             }
         }
-
-        for name in attr_names {
-            let value = self.get_property(&name).unwrap_or(String::new());
-            attributes.insert(name, value);
-        }
-
         attributes
     }
 
-    /// Returns the value of property `name`
+    /// Returns the value of property `name` (invalid UTF-8 is replaced)
     pub fn get_property(&self, name: &str) -> Option<String> {
-        let c_name = CString::new(name).unwrap();
+        self.property_bytes(name).map(|value| String::from_utf8_lossy(&value).into_owned())
+    }
+
+    /// Returns the value of property `name`, failing on invalid UTF-8
+    pub fn try_get_property(&self, name: &str) -> Result<Option<String>, Error> {
+        match self.property_bytes(name) {
+            None => Ok(None),
+            Some(value) => Ok(Some(String::from_utf8(value)?)),
+        }
+    }
+
+    fn property_bytes(&self, name: &str) -> Option<Vec<u8>> {
+        let c_name = CString::new(name).ok()?;
         let value_ptr = unsafe { xmlGetProp(self.node_ptr(), c_name.as_ptr() as *const u8) };
         if value_ptr.is_null() {
             return None;
         }
-        let c_value_string = unsafe { CStr::from_ptr(value_ptr as *const i8) };
-        let prop_str = str::from_utf8(c_value_string.to_bytes()).unwrap().to_owned();
         unsafe {
+            let value = xml_bytes(value_ptr);
             libc::free(value_ptr as *mut libc::c_void);
+            Some(value)
         }
-        Some(prop_str)
     }
 
     /// Alias for get_property
@@ -355,10 +1053,14 @@ impl Node {
 
     /// Return an attribute as a `Node` struct of type AttributeNode
     pub fn get_property_node(&self, name: &str) -> Option<Node> {
-        let c_name = CString::new(name).unwrap();
+        let c_name = CString::new(name).ok()?;
         unsafe {
-            let attr_node = xmlHasProp(self.node_ptr(), c_name.as_ptr() as *const u8);
-            Self::ptr_as_option(self, attr_node as xmlNodePtr)
+            let attr_node = xmlHasProp(self.node_ptr(), c_name.as_ptr() as *const u8) as xmlNodePtr;
+            // Leave out the default value declared in the DTD, it is not part of the tree
+            if attr_node.is_null() || (*attr_node).type_ != 2 {
+                return None;
+            }
+            Self::ptr_as_option(self, attr_node)
         }
     }
 
@@ -374,27 +1076,60 @@ impl Node {
     }
 
     /// Sets the value of property `name` to `value`
+    /// Nothing happens if `name` or `value` contain a NUL byte, see `try_set_property`.
     pub fn set_property(&mut self, name: &str, value: &str) {
-        let c_name = CString::new(name).unwrap();
-        let c_value = CString::new(value).unwrap();
+        let _ = self.try_set_property(name, value);
+    }
+
+    /// Sets the value of property `name` to `value`, failing if either contains a NUL byte
+    pub fn try_set_property(&mut self, name: &str, value: &str) -> Result<(), Error> {
+        let c_name = CString::new(name)?;
+        let c_value = CString::new(value)?;
         unsafe { xmlSetProp(self.node_ptr(), c_name.as_ptr() as *const u8, c_value.as_ptr() as *const u8) };
+        Ok(())
     }
 
-    /// Alias for set_property
+    /// Alias for set_property, nothing happens if `name` or `value` contain a NUL byte
     pub fn set_attribute(&mut self, name: &str, value: &str) {
         self.set_property(name, value)
     }
 
+    /// Returns the value of property `name` parsed with `FromStr`, `Ok(None)` if there is no such property.
+    /// The value is parsed as it is, surrounding whitespace is not trimmed.
+    pub fn get_property_as<T: FromStr>(&self, name: &str) -> Result<Option<T>, T::Err> {
+        match self.get_property(name) {
+            Some(value) => value.parse().map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns the value of property `name` as a boolean following the rules of `xs:boolean`:
+    /// `true` and `1` are true, `false` and `0` are false, surrounding whitespace is ignored.
+    /// Other values fail with the error of `bool::from_str`.
+    pub fn get_property_bool(&self, name: &str) -> Result<Option<bool>, ParseBoolError> {
+        match self.get_property(name) {
+            Some(value) => match value.trim() {
+                "true" | "1" => Ok(Some(true)),
+                "false" | "0" => Ok(Some(false)),
+                other => other.parse().map(Some),
+            },
+            None => Ok(None),
+        }
+    }
+
+    /// Sets the value of property `name` to the `Display` representation of `value`.
+    /// Nothing happens if `name` or the representation contain a NUL byte, see `try_set_property`.
+    pub fn set_property_from<T: Display>(&mut self, name: &str, value: &T) {
+        self.set_property(name, &value.to_string())
+    }
+
     /// Removes the property of given `name`
     pub fn remove_property(&mut self, name: &str) {
         // TODO: Should we make the API return a Result type here?
         // Current behaviour on failures: silently return (noop)
-        let c_name = CString::new(name).unwrap();
-        unsafe {
-            let attr_node = xmlHasProp(self.node_ptr(), c_name.as_ptr() as *const u8);
-            if !attr_node.is_null() {
-                xmlRemoveProp(attr_node);
-            }
+        // The attribute is unlinked, it is freed once there is no handle to it.
+        if let Some(mut attr_node) = self.get_property_node(name) {
+            attr_node.unlink();
         }
     }
 
@@ -403,15 +1138,106 @@ impl Node {
         self.remove_property(name)
     }
 
-    /// Serializes a `Node`
+    /// Returns the whitespace separated class names of the `class` attribute
+    pub fn get_class_names(&self) -> HashSet<String> {
+        self.get_class_list().into_iter().collect()
+    }
+
+    /// Returns true iff `name` is one of the class names of the `class` attribute
+    pub fn has_class(&self, name: &str) -> bool {
+        self.get_class_list().iter().any(|class| class == name)
+    }
+
+    /// Adds the class `name` to the `class` attribute, unless it is already there.
+    /// The attribute is rewritten with each class once, separated by single spaces.
+    pub fn add_class(&mut self, name: &str) {
+        let mut classes = self.get_class_list();
+        if !name.is_empty() && !classes.iter().any(|class| class == name) {
+            classes.push(name.to_owned());
+        }
+        self.set_class_list(&classes);
+    }
+
+    /// Removes the class `name` from the `class` attribute, the attribute is removed along with the last class.
+    /// The attribute is rewritten with each class once, separated by single spaces.
+    pub fn remove_class(&mut self, name: &str) {
+        let mut classes = self.get_class_list();
+        classes.retain(|class| class != name);
+        self.set_class_list(&classes);
+    }
+
+    /// Adds the class `name` if it is missing and removes it otherwise.
+    /// Returns true iff the class is present afterwards.
+    pub fn toggle_class(&mut self, name: &str) -> bool {
+        if self.has_class(name) {
+            self.remove_class(name);
+            false
+        } else {
+            self.add_class(name);
+            true
+        }
+    }
+
+    // The class names in order of their first occurrence
+    fn get_class_list(&self) -> Vec<String> {
+        let mut classes: Vec<String> = Vec::new();
+        if let Some(value) = self.get_property("class") {
+            for class in value.split_whitespace() {
+                if !classes.iter().any(|c| c == class) {
+                    classes.push(class.to_owned());
+                }
+            }
+        }
+        classes
+    }
+
+    fn set_class_list(&mut self, classes: &[String]) {
+        if classes.is_empty() {
+            self.remove_property("class");
+        } else {
+            self.set_property("class", &classes.join(" "));
+        }
+    }
+
+    /// Serializes a `Node` (invalid UTF-8 is replaced)
     pub fn to_string(&self, format: bool) -> String {
+        String::from_utf8_lossy(&self.dump_bytes(format)).into_owned()
+    }
+
+    /// Serializes a `Node`, failing on invalid UTF-8
+    pub fn try_to_string(&self, format: bool) -> Result<String, Error> {
+        Ok(String::from_utf8(self.dump_bytes(format))?)
+    }
+
+    /// Serializes the node itself including its tag, the same as `to_string(false)`
+    pub fn outer_xml(&self) -> String {
+        self.to_string(false)
+    }
+
+    /// Serializes the children of the node, without the node's own tag
+    pub fn inner_xml(&self) -> String {
+        let bytes: Vec<u8> = self.get_child_nodes().iter().flat_map(|child| child.dump_bytes(false)).collect();
+        String::from_utf8_lossy(&bytes).into_owned()
+    }
+
+    /// Returns `get_text_content` with `whitespace` applied, so comments and processing instructions
+    /// contribute no text, also when called on one of them.
+    pub fn inner_text(&self, whitespace: Whitespace) -> String {
+        let text = self.get_text_content();
+        match whitespace {
+            Whitespace::Preserve => text,
+            Whitespace::Trim => text.trim().to_owned(),
+            Whitespace::Normalize => text.split_whitespace().collect::<Vec<&str>>().join(" "),
+        }
+    }
+
+    fn dump_bytes(&self, format: bool) -> Vec<u8> {
         let format = if format {
             1
         } else {
             0
         };
-        let doc = &self.0.borrow().document;
-        let doc_ptr = doc.borrow().doc_ptr;
+        let doc_ptr = self.doc_ref().borrow().doc_ptr;
         unsafe {
             // allocate a buffer to dump into
             let buf = xmlBufferCreate();
@@ -422,12 +1248,10 @@ impl Node {
                         self.node_ptr(),
                         1, // level of indentation
                         format /* disable formatting */);
-            let result_ptr = xmlBufferContent(buf);
-            let c_string = CStr::from_ptr(result_ptr as *const i8);
-            let node_string = str::from_utf8(c_string.to_bytes()).unwrap().to_owned();
+            let node_bytes = xml_bytes(xmlBufferContent(buf));
             xmlBufferFree(buf);
 
-            node_string
+            node_bytes
         }
     }
 
@@ -435,30 +1259,43 @@ impl Node {
         if node_ptr.is_null() {
             None
         } else {
-            let new_node = Node::wrap(node_ptr, node.0.borrow().document.clone());
-            let doc = &node.0.borrow().document;
-            doc.borrow_mut().insert_node(node_ptr, new_node.clone());
-            Some(new_node)
+            Some(Node::wrap(node_ptr, node.doc_ref()))
         }
     }
 
     fn ptr_as_result(node: &Node, node_ptr: xmlNodePtr) -> Result<Node, ()> {
-        if node_ptr.is_null() {
-            Err(())
-        } else {
-            let new_node = Node::wrap(node_ptr, node.0.borrow().document.clone());
-            let doc = &node.0.borrow().document;
-            doc.borrow_mut().insert_node(node_ptr, new_node.clone());
-            Ok(new_node)
-        }
+        Node::ptr_as_option(node, node_ptr).ok_or(())
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::collections::HashMap;
+    use error::Error;
+    use xpath::Context;
+    use super::{Node, NodeType, Whitespace, xmlNodePtr};
+    use tree::ParseOptions;
     use tree::document::Document;
     use std::rc::Rc;
 
+    #[test]
+    fn raw_node_test() {
+        let doc = Document::parse("<root><child/></root>").unwrap();
+        let root = doc.get_root_element().unwrap();
+        let child = unsafe { Node::from_raw((*root.as_ptr()).children, &doc) }.unwrap();
+        assert_eq!(child.get_name(), "child");
+        assert_eq!(child.get_parent(), Some(root));
+
+        let other = Document::parse("<other/>").unwrap();
+        let other_root = other.get_root_element().unwrap();
+        assert!(unsafe { Node::from_raw(other_root.as_ptr(), &doc) }.is_none());
+        assert!(unsafe { Node::from_raw(doc.as_ptr() as xmlNodePtr, &doc) }.is_none());
+        let doc = Document::parse("<root xmlns:x=\"urn:x\" attr=\"1\"/>").unwrap();
+        let root_ptr = doc.get_root_element().unwrap().as_ptr();
+        assert!(unsafe { Node::from_raw((*root_ptr).properties as xmlNodePtr, &doc) }.is_none());
+        assert!(unsafe { Node::from_raw((*root_ptr).nsDef as xmlNodePtr, &doc) }.is_none());
+    }
+
     #[test]
     fn get_first_child_next_sibling_test() {
         let doc = Document::parse("<root><child></child><sibling></sibling></root>").unwrap();
@@ -469,6 +1306,62 @@ mod tests {
         assert!(sibling.is_some());
     }
 
+    #[test]
+    fn parse_fragment_test() {
+        let doc = Document::parse("<root xmlns:x=\"urn:x\"><p/></root>").unwrap();
+        let mut p = doc.get_root_element().unwrap().get_first_child().unwrap();
+
+        let nodes = p.parse_fragment("<x:b>bold</x:b> text", ParseOptions::NONET).unwrap();
+        assert_eq!(nodes.len(), 2);
+        assert_eq!(nodes[0].get_namespace_uri(), Some(String::from("urn:x")));
+        assert!(nodes[0].get_parent().is_none());
+        assert!(nodes[1].is_text_node());
+        assert!(p.parse_fragment("<b>unclosed", ParseOptions::NONET).is_err());
+
+        p.set_inner_xml("<x:b>bold</x:b> text", ParseOptions::NONET).unwrap();
+        assert_eq!(p.to_string(false), "<p><x:b>bold</x:b> text</p>");
+        assert!(p.set_inner_xml("<b>unclosed", ParseOptions::NONET).is_err());
+        assert_eq!(p.get_child_nodes().len(), 2);
+        p.set_inner_xml("", ParseOptions::NONET).unwrap();
+        assert_eq!(p.to_string(false), "<p/>");
+
+        p.set_inner_xml("<b>bold</b>  <i/>", ParseOptions::NOBLANKS).unwrap();
+        assert_eq!(p.to_string(false), "<p><b>bold</b><i/></p>");
+        let mut text = p.get_first_child().unwrap().get_first_child().unwrap();
+        assert!(text.set_inner_xml("", ParseOptions::NONET).is_err());
+        assert!(text.set_inner_xml("<i/>", ParseOptions::NONET).is_err());
+        assert_eq!(p.to_string(false), "<p><b>bold</b><i/></p>");
+    }
+
+    #[test]
+    fn inner_outer_xml_test() {
+        let doc = Document::parse("<root><p class=\"a\">Some <b>bold</b>\n   text<!-- note --></p><empty/></root>").unwrap();
+        let root = doc.get_root_element().unwrap();
+        let p = root.get_first_child().unwrap();
+        assert_eq!(p.outer_xml(), "<p class=\"a\">Some <b>bold</b>\n   text<!-- note --></p>");
+        assert_eq!(p.inner_xml(), "Some <b>bold</b>\n   text<!-- note -->");
+        assert_eq!(root.get_last_child().unwrap().inner_xml(), "");
+
+        assert_eq!(p.inner_text(Whitespace::Preserve), "Some bold\n   text");
+        assert_eq!(root.inner_text(Whitespace::Trim), "Some bold\n   text");
+        assert_eq!(p.inner_text(Whitespace::Normalize), "Some bold text");
+    }
+
+    #[test]
+    fn inner_text_mixed_content_test() {
+        let doc = Document::parse("<p> One <b>two</b><!-- no -->\n<?pi no?><![CDATA[ three ]]>\t</p>").unwrap();
+        let p = doc.get_root_element().unwrap();
+        assert_eq!(p.inner_text(Whitespace::Preserve), " One two\n three \t");
+        assert_eq!(p.inner_text(Whitespace::Trim), "One two\n three");
+        assert_eq!(p.inner_text(Whitespace::Normalize), "One two three");
+        let children = p.get_child_nodes();
+        for &whitespace in &[Whitespace::Preserve, Whitespace::Trim, Whitespace::Normalize] {
+            // The comment and the processing instruction
+            assert_eq!(children[2].inner_text(whitespace), "");
+            assert_eq!(children[4].inner_text(whitespace), "");
+        }
+    }
+
     #[test]
     fn get_child_nodes_test() {
         let doc = Document::parse("<root><child></child><sibling></sibling></root>").unwrap();
@@ -489,6 +1382,244 @@ mod tests {
         assert_eq!("<root><lol>child</lol><lol>sibling</lol></root>", node.to_string(false));
     }
 
+    #[test]
+    fn navigation_does_not_retain_document_test() {
+        let doc = Document::parse("<root><child></child><sibling></sibling></root>").unwrap();
+        {
+            let node = doc.get_root_element().unwrap();
+            assert_eq!(2, node.get_child_nodes().len());
+            assert!(node.get_last_child().is_some());
+        }
+        // Only `doc` itself and the temporary from `doc_ref` are left
+        assert_eq!(2, Rc::strong_count(&doc.doc_ref()));
+    }
+
+    #[test]
+    fn clone_subtree_test() {
+        let doc = Document::parse("<root><child attribute=\"value\"><inner/></child></root>").unwrap();
+        let mut root = doc.get_root_element().unwrap();
+        let child = root.get_first_child().unwrap();
+        let mut deep = child.clone_subtree(true).unwrap();
+        let mut shallow = child.clone_subtree(false).unwrap();
+        assert!(deep != child);
+        assert_eq!(deep.to_string(false), "<child attribute=\"value\"><inner/></child>");
+        assert_eq!(shallow.to_string(false), "<child attribute=\"value\"/>");
+        root.add_child(&mut deep).unwrap();
+        root.add_child(&mut shallow).unwrap();
+        assert_eq!(root.get_child_elements().len(), 3);
+        // An unused copy is freed with the document
+        child.clone_subtree(true).unwrap();
+    }
+
+    #[test]
+    fn set_content_test() {
+        let doc = Document::parse("<root><child>old</child></root>").unwrap();
+        let mut node = doc.get_root_element().unwrap();
+        let old_child = node.get_first_child().unwrap();
+        node.set_content("a & b < c");
+        assert_eq!(node.to_string(false), "<root>a &amp; b &lt; c</root>");
+        assert_eq!(node.get_content(), "a & b < c");
+        assert_eq!(old_child.get_content(), "old");
+
+        node.set_raw_content("&lt;b&gt; &amp; more");
+        assert_eq!(node.get_content(), "<b> & more");
+        let mut text = node.get_first_child().unwrap();
+        text.set_content("a & b");
+        assert_eq!(node.to_string(false), "<root>a &amp; b</root>");
+
+        let doc = Document::parse("<!DOCTYPE root [<!ENTITY e \"value\">]><root>&e;</root>").unwrap();
+        let mut reference = doc.get_root_element().unwrap().get_first_child().unwrap();
+        assert_eq!(reference.get_type(), Some(NodeType::EntityRefNode));
+        assert_eq!(reference.try_set_content("other"), Err(Error::ReadOnlyNode));
+        reference.set_raw_content("other");
+        assert_eq!(doc.get_root_element().unwrap().get_content(), "value");
+    }
+
+    #[test]
+    fn get_text_content_test() {
+        let doc = Document::parse("<root>one<!-- comment --><?pi data?><child>two<![CDATA[three]]></child>four</root>").unwrap();
+        let node = doc.get_root_element().unwrap();
+        assert_eq!(node.get_text_content(), "onetwothreefour");
+        assert_eq!(node.get_child_text(), "onefour");
+        let comment = node.get_child_nodes()[1].clone();
+        assert_eq!(comment.get_text_content(), "");
+    }
+
+    #[test]
+    fn source_location_test() {
+        let xml = "<root>\n  <child/>\n\n  <child xml:base=\"sub/\"><inner/></child>\n</root>";
+        let doc = Document::parse_with_options(xml, "http://example.com/doc.xml", "utf-8", ParseOptions::DEFAULT_XML).unwrap();
+        let node = doc.get_root_element().unwrap();
+        let children = node.get_child_elements();
+        assert_eq!(node.get_line_number(), Some(1));
+        assert_eq!(children[0].get_line_number(), Some(2));
+        assert_eq!(children[1].get_line_number(), Some(4));
+        assert_eq!(node.base_uri(), Some(String::from("http://example.com/doc.xml")));
+        let inner = children[1].get_first_child().unwrap();
+        assert_eq!(inner.base_uri(), Some(String::from("http://example.com/sub/")));
+    }
+
+    #[test]
+    fn big_line_number_test() {
+        let xml = format!("<root>{}<child/></root>", "\n".repeat(70000));
+        let doc = Document::parse_with_options(&xml, "", "utf-8", ParseOptions::DEFAULT_XML | ParseOptions::BIG_LINES).unwrap();
+        let child = doc.get_root_element().unwrap().get_child_elements().pop().unwrap();
+        assert_eq!(child.get_line_number(), Some(70001));
+
+        let doc = Document::parse_with_options(&xml, "", "utf-8", ParseOptions::DEFAULT_XML | ParseOptions::HUGE).unwrap();
+        let child = doc.get_root_element().unwrap().get_child_elements().pop().unwrap();
+        assert_eq!(child.get_line_number(), Some(65535));
+    }
+
+    #[test]
+    fn get_elements_by_tag_name_test() {
+        let doc = Document::parse(r#"<root xmlns:a="urn:a"><item n="1"><item n="2"/></item><a:item n="3"/><other><item n="4"/></other></root>"#).unwrap();
+        let root = doc.get_root_element().unwrap();
+        let numbers = |nodes: Vec<Node>| nodes.iter().map(|n| n.get_property("n").unwrap()).collect::<Vec<String>>();
+        assert_eq!(numbers(root.get_elements_by_tag_name("item")), vec!["1", "2", "4"]);
+        assert_eq!(numbers(root.get_elements_by_tag_name("a:item")), vec!["3"]);
+        assert_eq!(root.get_elements_by_tag_name("*").len(), 5);
+        assert!(root.get_elements_by_tag_name("root").is_empty());
+
+        assert_eq!(numbers(root.get_elements_by_tag_name_ns("urn:a", "item")), vec!["3"]);
+        assert_eq!(numbers(root.get_elements_by_tag_name_ns("", "item")), vec!["1", "2", "4"]);
+        assert_eq!(numbers(root.get_elements_by_tag_name_ns("*", "item")), vec!["1", "2", "3", "4"]);
+        assert_eq!(root.get_elements_by_tag_name_ns("urn:a", "*").len(), 1);
+    }
+
+    #[test]
+    fn select_test() {
+        let doc = Document::parse(r#"<html><body>
+            <div class="item first"><a href="http://example.com">1</a><span><a href="http://nested.com">2</a></span></div>
+            <div class="item"><a href="/relative">3</a><a href="https://example.com">4</a></div>
+            <div class="other"><a href="http://other.com">5</a></div>
+        </body></html>"#).unwrap();
+        let root = doc.get_root_element().unwrap();
+        let texts = |nodes: Vec<Node>| nodes.iter().map(|n| n.get_content()).collect::<Vec<String>>();
+        assert_eq!(texts(root.select("div.item > a[href^='http']").unwrap()), vec!["1", "4"]);
+        assert_eq!(texts(root.select("div.item a:last-child").unwrap()), vec!["2", "4"]);
+        assert_eq!(texts(root.select(".other a, .first span a").unwrap()), vec!["2", "5"]);
+
+        // Only descendants take part in matching, the node itself is never selected
+        let second = &root.select("div:nth-of-type(2)").unwrap()[0];
+        assert_eq!(texts(second.select("a[href$='.com']").unwrap()), vec!["4"]);
+        assert!(second.select("body a").unwrap().is_empty());
+        assert!(second.select("div").unwrap().is_empty());
+
+        assert!(root.select("div >").is_err());
+    }
+
+    #[test]
+    fn class_list_test() {
+        let doc = Document::parse("<root class=\"  first\tsecond  first \"/>").unwrap();
+        let mut root = doc.get_root_element().unwrap();
+        assert_eq!(root.get_class_names().len(), 2);
+        assert!(root.has_class("second"));
+        assert!(!root.has_class("sec"));
+
+        root.add_class("third");
+        assert_eq!(root.get_property("class"), Some(String::from("first second third")));
+        root.remove_class("first");
+        assert_eq!(root.get_property("class"), Some(String::from("second third")));
+        assert!(!root.toggle_class("second"));
+        assert!(root.toggle_class("fourth"));
+        assert_eq!(root.get_property("class"), Some(String::from("third fourth")));
+
+        root.remove_class("third");
+        root.remove_class("fourth");
+        assert_eq!(root.get_property("class"), None);
+        root.add_class("only");
+        assert_eq!(root.get_property("class"), Some(String::from("only")));
+    }
+
+    #[test]
+    fn typed_property_test() {
+        let doc = Document::parse("<root count=\"42\" ratio=\"x\" enabled=\" 1 \" visible=\"false\" checked=\"yes\"/>").unwrap();
+        let mut root = doc.get_root_element().unwrap();
+        assert_eq!(root.get_property_as::<u32>("count"), Ok(Some(42)));
+        assert!(root.get_property_as::<f64>("ratio").is_err());
+        assert_eq!(root.get_property_as::<u32>("missing"), Ok(None));
+
+        assert_eq!(root.get_property_bool("enabled"), Ok(Some(true)));
+        assert_eq!(root.get_property_bool("visible"), Ok(Some(false)));
+        assert!(root.get_property_bool("checked").is_err());
+        assert_eq!(root.get_property_bool("missing"), Ok(None));
+
+        root.set_property_from("count", &7.5);
+        assert_eq!(root.get_property_as::<f64>("count"), Ok(Some(7.5)));
+        root.set_property_from("visible", &true);
+        assert_eq!(root.get_property_bool("visible"), Ok(Some(true)));
+    }
+
+    #[test]
+    fn get_path_test() {
+        let doc = Document::parse("<root><child/><child attr=\"value\">text</child><other/></root>").unwrap();
+        let root = doc.get_root_element().unwrap();
+        let children = root.get_child_nodes();
+        assert_eq!(root.get_path(), Some(String::from("/root")));
+        assert_eq!(children[1].get_path(), Some(String::from("/root/child[2]")));
+        let attr = children[1].get_property_node("attr").unwrap();
+        assert_eq!(attr.get_path(), Some(String::from("/root/child[2]/@attr")));
+        assert_eq!(attr.get_parent().unwrap(), children[1]);
+        assert_eq!(root.get_parent().unwrap().get_type(), Some(NodeType::DocumentNode));
+    }
+
+    #[test]
+    fn recursively_remove_namespaces_test() {
+        let doc = Document::parse("<x:root xmlns:x=\"urn:x\"><x:child x:attr=\"1\"/></x:root>").unwrap();
+        doc.as_node().recursively_remove_namespaces();
+        let root = doc.get_root_element().unwrap();
+        let child = root.get_first_child().unwrap();
+        assert_eq!(root.get_namespace_uri(), None);
+        assert_eq!(child.get_namespace_uri(), None);
+        assert_eq!(child.get_attribute("attr"), Some(String::from("1")));
+        assert_eq!(doc.to_string(false), "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<root xmlns:x=\"urn:x\"><child attr=\"1\"/></root>\n");
+    }
+
+    #[test]
+    fn get_path_with_namespaces_test() {
+        let doc = Document::parse(r#"<root xmlns="urn:root" xmlns:o="urn:other"><o:child/><o:child o:attr="1"><plain xmlns=""/></o:child></root>"#).unwrap();
+        let root = doc.get_root_element().unwrap();
+        let child = root.get_child_nodes()[1].clone();
+        let attr = child.get_property_node("attr").unwrap();
+        let plain = child.get_first_child().unwrap();
+
+        let mut namespaces = HashMap::new();
+        namespaces.insert(String::from("r"), String::from("urn:root"));
+        namespaces.insert(String::from("x"), String::from("urn:other"));
+        assert_eq!(child.get_path_with_namespaces(&namespaces), "/r:root/x:child[2]");
+        assert_eq!(attr.get_path_with_namespaces(&namespaces), "/r:root/x:child[2]/@x:attr");
+        assert_eq!(plain.get_path_with_namespaces(&namespaces), "/r:root/x:child[2]/plain");
+
+        namespaces.remove("x");
+        let path = child.get_path_with_namespaces(&namespaces);
+        assert_eq!(path, "/r:root/*[local-name()='child' and namespace-uri()='urn:other'][2]");
+
+        let context = Context::new(&doc).unwrap();
+        context.register_namespace("r", "urn:root").unwrap();
+        assert_eq!(context.evaluate(&path).unwrap().get_nodes_as_vec(), vec![child]);
+
+        let doc = Document::parse(r#"<root xmlns:q="urn:it's"><q:item/></root>"#).unwrap();
+        let item = doc.get_root_element().unwrap().get_first_child().unwrap();
+        let path = item.get_path_with_namespaces(&HashMap::new());
+        assert_eq!(path, r#"/root/*[local-name()='item' and namespace-uri()="urn:it's"]"#);
+        assert_eq!(Context::new(&doc).unwrap().evaluate(&path).unwrap().get_nodes_as_vec(), vec![item]);
+    }
+
+    #[test]
+    fn interior_nul_test() {
+        let doc = Document::parse("<root/>").unwrap();
+        let mut node = doc.get_root_element().unwrap();
+        assert_eq!(node.try_set_property("key", "a\0b"), Err(Error::InteriorNul(1)));
+        node.set_property("key", "a\0b");
+        assert_eq!(node.get_property("key"), None);
+        assert!(node.try_append_text("te\0xt").is_err());
+        assert_eq!(node.try_get_content(), Ok(String::new()));
+        assert!(node.try_set_name("ro\0ot").is_err());
+        assert_eq!(node.try_get_name(), Ok(String::from("root")));
+        assert!(node.get_property_node("ke\0y").is_none());
+    }
+
     #[test]
     fn unlink_test() {
         let doc = Document::parse("<root><child>child</child><sibling>sibling</sibling></root>").unwrap();
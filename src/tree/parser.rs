@@ -0,0 +1,680 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::ffi::{CStr, CString};
+use std::fs::File;
+use std::io::{self, Read};
+use std::os::raw::{c_char, c_int, c_void};
+use std::ptr;
+use std::slice;
+use std::sync::OnceLock;
+
+use libxml2::{xmlCtxtReadMemory,
+              xmlCtxtReadFile,
+              xmlCtxtReadIO,
+              xmlNewParserCtxt,
+              xmlFreeParserCtxt,
+              xmlResetLastError,
+              xmlGetExternalEntityLoader,
+              xmlSetExternalEntityLoader,
+              xmlExternalEntityLoader,
+              xmlParserInputBufferCreateMem,
+              xmlFreeParserInputBuffer,
+              xmlNewIOInputStream,
+              xmlStrdup,
+              xmlCharEncoding_XML_CHAR_ENCODING_NONE,
+              xmlStopParser,
+              xmlFreeDoc,
+              xmlChar,
+              startElementNsSAX2Func,
+              endElementNsSAX2Func,
+              getEntitySAXFunc,
+              xmlGetDocEntity,
+              xmlEntityPtr,
+              xmlEntityType_XML_INTERNAL_GENERAL_ENTITY,
+              xmlCatalogAddLocal,
+              xmlSetStructuredErrorFunc,
+              __xmlStructuredError,
+              __xmlStructuredErrorContext,
+              xmlErrorPtr,
+              xmlDocPtr,
+              xmlParserCtxtPtr,
+              xmlParserInputPtr};
+
+use error::Error;
+use tree::{ParseOptions, XmlInput, XmlError, init_parser, xml_bytes};
+use tree::document::Document;
+
+/// Resolves an external DTD or entity from its system ID (URL) and public ID.
+/// Returns the content, or `None` to refuse loading it.
+pub type EntityLoader = dyn Fn(&str, Option<&str>) -> Option<Vec<u8>>;
+
+/// Limits for parsing untrusted input, see `Parser::set_security_policy`.
+/// A document exceeding one of them is rejected with the matching `Error` as cause.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SecurityPolicy {
+    /// Maximum nesting depth of elements
+    pub max_depth: usize,
+    /// Maximum number of elements
+    pub max_nodes: usize,
+    /// Maximum size of the input in bytes, counted while reading
+    pub max_document_bytes: usize,
+    /// Maximum number of entity references, a reference counts together with all references in the value of its entity
+    pub max_entity_expansions: usize,
+}
+
+impl Default for SecurityPolicy {
+    fn default() -> Self {
+        SecurityPolicy {
+            max_depth: 256,
+            max_nodes: 1_000_000,
+            max_document_bytes: 10 * 1024 * 1024,
+            max_entity_expansions: 10_000,
+        }
+    }
+}
+
+impl SecurityPolicy {
+    /// A policy without any limits of its own, network access and external entities are still forbidden
+    pub fn unlimited() -> SecurityPolicy {
+        SecurityPolicy {
+            max_depth: usize::MAX,
+            max_nodes: usize::MAX,
+            max_document_bytes: usize::MAX,
+            max_entity_expansions: usize::MAX,
+        }
+    }
+}
+
+/// Parses documents with a fixed configuration.
+///
+/// ```ignore
+/// let mut parser = Parser::new();
+/// parser.set_options(ParseOptions::DEFAULT_XML | ParseOptions::DTDLOAD);
+/// parser.set_entity_loader(|url, _| bundle.get(url).cloned());
+/// let doc = parser.parse(Path::new("config.xml"))?;
+/// ```
+pub struct Parser {
+    options: ParseOptions,
+    url: String,
+    encoding: String,
+    entity_loader: Option<Box<EntityLoader>>,
+    security_policy: Option<SecurityPolicy>,
+    catalogs: Vec<String>,
+}
+
+impl Default for Parser {
+    fn default() -> Self {
+        Parser::new()
+    }
+}
+
+impl Parser {
+    /// Creates a parser with `ParseOptions::DEFAULT_XML` for UTF-8 input
+    pub fn new() -> Parser {
+        Parser {
+            options: ParseOptions::DEFAULT_XML,
+            url: String::new(),
+            encoding: String::from("utf-8"),
+            entity_loader: None,
+            security_policy: None,
+            catalogs: Vec::new(),
+        }
+    }
+
+    pub fn set_options(&mut self, options: ParseOptions) {
+        self.options = options;
+    }
+
+    /// Sets the base URL of documents parsed from strings, relative system IDs are resolved against it
+    pub fn set_url(&mut self, url: &str) {
+        self.url = url.to_owned();
+    }
+
+    /// Sets the encoding of the input, UTF-8 by default.
+    /// The encoding of a `File` is detected from its contents unless an encoding other than UTF-8 is set.
+    pub fn set_encoding(&mut self, encoding: &str) {
+        self.encoding = encoding.to_owned();
+    }
+
+    /// Loads external DTDs and entities through `loader` instead of from wherever their system ID points.
+    /// This only happens with `ParseOptions::DTDLOAD`, `DTDVALID` or `NOENT`, a refused resource is reported as error.
+    /// The loader must not panic.
+    pub fn set_entity_loader<F>(&mut self, loader: F) where F: Fn(&str, Option<&str>) -> Option<Vec<u8>> + 'static {
+        self.entity_loader = Some(Box::new(loader));
+    }
+
+    /// Enforces `policy` on every document parsed.
+    /// The parser then never uses the network or `ParseOptions::HUGE` and refuses external DTDs
+    /// and entities unless they are served by the entity loader.
+    /// Files are read as they are, compressed files are not decompressed.
+    pub fn set_security_policy(&mut self, policy: SecurityPolicy) {
+        self.security_policy = Some(policy);
+    }
+
+    /// Adds the catalog file `filename`, consulted by this parser before the global catalog, see `catalog`
+    pub fn add_catalog(&mut self, filename: &str) {
+        self.catalogs.push(filename.to_owned());
+    }
+
+    /// Parses a document from a string or a file.
+    /// A `File` is read as raw bytes, failing if it cannot be read.
+    pub fn parse<R: XmlInput + ?Sized>(&self, r: &R) -> Result<Document, Vec<XmlError>> {
+        let c_encoding = c_string_arg(&self.encoding)?;
+        let mut options = self.options;
+        if self.security_policy.is_some() {
+            // SAX1 would bypass the element callbacks counting depth and nodes
+            options.insert(ParseOptions::NONET);
+            options.remove(ParseOptions::HUGE | ParseOptions::SAX1);
+        }
+        let options = options.bits;
+        let max_bytes = self.security_policy.map(|policy| policy.max_document_bytes);
+        let mut reader = r.reader().map(|reader| (reader, self.url.clone()));
+        if let (None, Some(_), true) = (&reader, max_bytes, r.is_path()) {
+            // Files are read like a `File`, so their size is counted while reading, whatever kind of file they are
+            let filename = r.data();
+            match File::open(&filename) {
+                Ok(file) => reader = Some((Box::new(file), filename)),
+                Err(err) => return Err(vec![read_error(&err)]),
+            }
+        }
+        if let Some((reader, url)) = reader {
+            let c_url = c_string_arg(&url)?;
+            // Raw bytes are declared in the document, forcing UTF-8 would override the declaration
+            let encoding_ptr = if self.encoding.eq_ignore_ascii_case("utf-8") { ptr::null() } else { c_encoding.as_ptr() };
+            let mut input = ReadInput { reader, read: 0, max_bytes: max_bytes.unwrap_or(usize::MAX), error: None };
+            let input_ptr = &mut input as *mut ReadInput as *mut c_void;
+            let result = self.parse_handler(|ctxt| unsafe {
+                xmlCtxtReadIO(ctxt, Some(read_input), None, input_ptr, c_url.as_ptr(), encoding_ptr, options)
+            });
+            match input.error {
+                // Whatever was recovered from the part that could be read is not handed out
+                Some(err) => {
+                    let mut errors = result.err().unwrap_or_default();
+                    errors.push(err);
+                    Err(errors)
+                },
+                None => result,
+            }
+        } else if r.is_path() {
+            let filename = r.data();
+            let c_filename = c_string_arg(&filename)?;
+            self.parse_handler(|ctxt| unsafe { xmlCtxtReadFile(ctxt, c_filename.as_ptr(), c_encoding.as_ptr(), options) })
+        } else {
+            // The buffer is passed with its length, so it does not need to be NUL terminated
+            let data = r.data();
+            if let Some(ref policy) = self.security_policy {
+                if data.len() > policy.max_document_bytes {
+                    return Err(vec![XmlError::from(Error::DocumentSizeLimit(policy.max_document_bytes))]);
+                }
+            }
+            let c_url = c_string_arg(&self.url)?;
+            self.parse_handler(|ctxt| unsafe { xmlCtxtReadMemory(ctxt, data.as_ptr() as *const c_char, data.len() as c_int, c_url.as_ptr(), c_encoding.as_ptr(), options) })
+        }
+    }
+
+    /// Runs `parse_closure` on a fresh parser context.
+    /// Errors are collected through a handler installed on that context only,
+    /// so several threads can parse at the same time.
+    fn parse_handler<F>(&self, parse_closure: F) -> Result<Document, Vec<XmlError>> where F: FnOnce(xmlParserCtxtPtr) -> xmlDocPtr {
+        init_parser();
+        let c_catalogs = self.catalogs.iter().map(|catalog| c_string_arg(catalog)).collect::<Result<Vec<_>, _>>()?;
+        unsafe {
+            let ctxt = xmlNewParserCtxt();
+            if ctxt.is_null() {
+                return Err(vec![XmlError { message: String::from("Could not create parser context"), cause: None }]);
+            }
+            let sax = (*ctxt).sax;
+            let mut state = ParseState {
+                ctxt,
+                errors: vec![],
+                entity_loader: self.entity_loader.as_deref(),
+                policy: self.security_policy,
+                limit_exceeded: false,
+                depth: 0,
+                nodes: 0,
+                expansions: 0,
+                expansion_sizes: HashMap::new(),
+                start_element: (*sax).startElementNs,
+                end_element: (*sax).endElementNs,
+                get_entity: (*sax).getEntity,
+                catalogs: &c_catalogs,
+                catalogs_added: false,
+            };
+            let state_ptr = &mut state as *mut ParseState as *mut c_void;
+            xmlResetLastError();
+            (*ctxt)._private = state_ptr;
+            (*sax).serror = Some(error_vec_pusher);
+            if state.policy.is_some() {
+                (*sax).startElementNs = Some(start_element_checked);
+                (*sax).endElementNs = Some(end_element_checked);
+                (*sax).getEntity = Some(get_entity_checked);
+            }
+            RUNNING.with(|running| running.borrow_mut().push(state_ptr));
+            let mut doc_ptr = parse_closure(ctxt);
+            RUNNING.with(|running| running.borrow_mut().retain(|&running| running != state_ptr));
+            (*ctxt)._private = ptr::null_mut();
+            xmlFreeParserCtxt(ctxt);
+            if state.limit_exceeded && !doc_ptr.is_null() {
+                // Whatever was parsed up to the limit is not handed out
+                xmlFreeDoc(doc_ptr);
+                doc_ptr = ptr::null_mut();
+            }
+            Document::handle_result_ptrs(doc_ptr, state.errors)
+        }
+    }
+}
+
+// The input of a parse reading from `XmlInput::reader`, the context of `read_input`
+struct ReadInput<'a> {
+    reader: Box<dyn Read + 'a>,
+    read: usize,
+    max_bytes: usize,
+    error: Option<XmlError>,
+}
+
+// Read callback of `xmlCtxtReadIO`, a read error or more than `max_bytes` ends the input
+unsafe extern "C" fn read_input(context: *mut c_void, buffer: *mut c_char, len: c_int) -> c_int {
+    let input = &mut *(context as *mut ReadInput);
+    let buffer = slice::from_raw_parts_mut(buffer as *mut u8, len as usize);
+    loop {
+        match input.reader.read(buffer) {
+            Ok(read) => {
+                input.read = input.read.saturating_add(read);
+                if input.read > input.max_bytes {
+                    input.error = Some(XmlError::from(Error::DocumentSizeLimit(input.max_bytes)));
+                    return -1;
+                }
+                return read as c_int;
+            },
+            Err(ref err) if err.kind() == io::ErrorKind::Interrupted => {},
+            Err(err) => {
+                input.error = Some(read_error(&err));
+                return -1;
+            },
+        }
+    }
+}
+
+fn read_error(err: &io::Error) -> XmlError {
+    XmlError { message: format!("Could not read the input: {}", err), cause: None }
+}
+
+// Parse arguments containing a NUL byte are reported like any other parse error
+fn c_string_arg(arg: &str) -> Result<CString, Vec<XmlError>> {
+    CString::new(arg).map_err(|err| vec![XmlError::from(Error::from(err))])
+}
+
+// The state of a running parse, the `_private` field of its parser context points to it.
+// libxml2 copies `_private` to the contexts it creates for parsing external entities.
+struct ParseState<'a> {
+    ctxt: xmlParserCtxtPtr,
+    errors: Vec<XmlError>,
+    entity_loader: Option<&'a EntityLoader>,
+    policy: Option<SecurityPolicy>,
+    limit_exceeded: bool,
+    depth: usize,
+    nodes: usize,
+    expansions: u64,
+    // The number of references an entity expands to, by entity
+    expansion_sizes: HashMap<xmlEntityPtr, u64>,
+    // The callbacks of libxml2 building the tree
+    start_element: startElementNsSAX2Func,
+    end_element: endElementNsSAX2Func,
+    get_entity: getEntitySAXFunc,
+    catalogs: &'a [CString],
+    catalogs_added: bool,
+}
+
+thread_local! {
+    // The states of the parses running on this thread
+    static RUNNING: RefCell<Vec<*mut c_void>> = const { RefCell::new(Vec::new()) };
+}
+
+impl<'a> ParseState<'a> {
+    // The state of `ctxt`, if the context belongs to a `Parser`.
+    // Contexts created through `sys` may use `_private` for something else.
+    unsafe fn from_ctxt(ctxt: xmlParserCtxtPtr) -> Option<&'a mut ParseState<'a>> {
+        if ctxt.is_null() || (*ctxt)._private.is_null() {
+            return None;
+        }
+        let state_ptr = (*ctxt)._private;
+        if RUNNING.with(|running| running.borrow().contains(&state_ptr)) {
+            Some(&mut *(state_ptr as *mut ParseState))
+        } else {
+            None
+        }
+    }
+
+    // Stops the parse with the first limit exceeded
+    unsafe fn exceed(&mut self, ctxt: xmlParserCtxtPtr, limit: Error) {
+        if !self.limit_exceeded {
+            self.limit_exceeded = true;
+            self.errors.push(XmlError::from(limit));
+        }
+        xmlStopParser(ctxt);
+        if ctxt != self.ctxt {
+            xmlStopParser(self.ctxt);
+        }
+    }
+
+    // The number of references `entity` expands to, itself included
+    unsafe fn expansion_size(&mut self, entity: xmlEntityPtr) -> u64 {
+        if let Some(&size) = self.expansion_sizes.get(&entity) {
+            return size;
+        }
+        // Guards against reference loops, which libxml2 reports itself
+        self.expansion_sizes.insert(entity, 1);
+        let mut size: u64 = 1;
+        if (*entity).etype == xmlEntityType_XML_INTERNAL_GENERAL_ENTITY {
+            let doc = (*self.ctxt).myDoc;
+            for name in entity_references(&xml_bytes((*entity).content)) {
+                let referenced = match CString::new(name) {
+                    Ok(name) => xmlGetDocEntity(doc, name.as_ptr() as *const xmlChar),
+                    Err(_) => continue,
+                };
+                if !referenced.is_null() {
+                    size = size.saturating_add(self.expansion_size(referenced));
+                }
+            }
+        }
+        self.expansion_sizes.insert(entity, size);
+        size
+    }
+}
+
+// The names of the entities referenced in an entity value, without character references
+fn entity_references(value: &[u8]) -> Vec<Vec<u8>> {
+    let mut names = Vec::new();
+    let mut rest = value;
+    while let Some(start) = rest.iter().position(|&byte| byte == b'&') {
+        rest = &rest[start + 1..];
+        match rest.iter().position(|&byte| byte == b';') {
+            Some(end) => {
+                if rest.first() != Some(&b'#') {
+                    names.push(rest[..end].to_vec());
+                }
+                rest = &rest[end + 1..];
+            },
+            None => break,
+        }
+    }
+    names
+}
+
+// Counts elements and nesting depth before handing the element to libxml2
+unsafe extern "C" fn start_element_checked(ctx: *mut c_void, localname: *const xmlChar, prefix: *const xmlChar, uri: *const xmlChar,
+                                           nb_namespaces: c_int, namespaces: *mut *const xmlChar,
+                                           nb_attributes: c_int, nb_defaulted: c_int, attributes: *mut *const xmlChar) {
+    let ctxt = ctx as xmlParserCtxtPtr;
+    let state = match ParseState::from_ctxt(ctxt) {
+        Some(state) => state,
+        None => return,
+    };
+    if let Some(policy) = state.policy {
+        state.depth += 1;
+        state.nodes += 1;
+        if state.depth > policy.max_depth {
+            return state.exceed(ctxt, Error::DepthLimit(policy.max_depth));
+        }
+        if state.nodes > policy.max_nodes {
+            return state.exceed(ctxt, Error::NodeLimit(policy.max_nodes));
+        }
+    }
+    if let Some(start_element) = state.start_element {
+        start_element(ctx, localname, prefix, uri, nb_namespaces, namespaces, nb_attributes, nb_defaulted, attributes);
+    }
+}
+
+// Counts the references to an entity together with the references in its value.
+// References inside entity values are looked up again while libxml2 expands them,
+// only those in the document itself are counted.
+unsafe extern "C" fn get_entity_checked(ctx: *mut c_void, name: *const xmlChar) -> xmlEntityPtr {
+    let ctxt = ctx as xmlParserCtxtPtr;
+    let state = match ParseState::from_ctxt(ctxt) {
+        Some(state) => state,
+        None => return ptr::null_mut(),
+    };
+    let entity = match state.get_entity {
+        Some(get_entity) => get_entity(ctx, name),
+        None => return ptr::null_mut(),
+    };
+    if let (Some(policy), false) = (state.policy, entity.is_null()) {
+        if (*ctxt).depth == 0 {
+            state.expansions = state.expansions.saturating_add(state.expansion_size(entity));
+            if state.expansions > policy.max_entity_expansions as u64 {
+                state.exceed(ctxt, Error::EntityExpansionLimit(policy.max_entity_expansions));
+                return ptr::null_mut();
+            }
+        }
+    }
+    entity
+}
+
+unsafe extern "C" fn end_element_checked(ctx: *mut c_void, localname: *const xmlChar, prefix: *const xmlChar, uri: *const xmlChar) {
+    let state = match ParseState::from_ctxt(ctx as xmlParserCtxtPtr) {
+        Some(state) => state,
+        None => return,
+    };
+    state.depth = state.depth.saturating_sub(1);
+    if let Some(end_element) = state.end_element {
+        end_element(ctx, localname, prefix, uri);
+    }
+}
+
+/// Structured error handler installed on a parser context.
+/// libxml2 passes the context itself, its `_private` field points to the `ParseState` of the running parse.
+extern "C" fn error_vec_pusher(ctxt_ptr: *mut c_void, libxml_error: xmlErrorPtr) {
+    unsafe {
+        let ctxt = ctxt_ptr as xmlParserCtxtPtr;
+        let state = (*ctxt)._private as *mut ParseState;
+        if state.is_null() {
+            return;
+        }
+        let msg = String::from_utf8_lossy(&xml_bytes((*libxml_error).message as *const u8)).into_owned();
+        (*state).errors.push(XmlError { message: msg, cause: None });
+    }
+}
+
+/// Runs `f` with the errors libxml2 reports on this thread collected.
+/// This is for functions creating a parser context of their own.
+pub(crate) fn collect_errors<T, F>(f: F) -> (T, Vec<XmlError>) where F: FnOnce() -> T {
+    init_parser();
+    let mut errors: Vec<XmlError> = vec![];
+    unsafe {
+        // The handler of this thread may be in use by the application, it is put back afterwards
+        let (previous, previous_context) = (*__xmlStructuredError(), *__xmlStructuredErrorContext());
+        xmlSetStructuredErrorFunc(&mut errors as *mut Vec<XmlError> as *mut c_void, Some(error_collector));
+        let result = f();
+        xmlSetStructuredErrorFunc(previous_context, previous);
+        (result, errors)
+    }
+}
+
+extern "C" fn error_collector(errors_ptr: *mut c_void, libxml_error: xmlErrorPtr) {
+    unsafe {
+        let errors = errors_ptr as *mut Vec<XmlError>;
+        let msg = String::from_utf8_lossy(&xml_bytes((*libxml_error).message as *const u8)).into_owned();
+        (*errors).push(XmlError { message: msg, cause: None });
+    }
+}
+
+// The entity loader libxml2 had before `install_entity_loader` replaced it
+static DEFAULT_ENTITY_LOADER: OnceLock<xmlExternalEntityLoader> = OnceLock::new();
+
+/// Makes `entity_loader` the global entity loader of libxml2, called once from `init_parser`
+pub(crate) unsafe fn install_entity_loader() {
+    DEFAULT_ENTITY_LOADER.get_or_init(|| xmlGetExternalEntityLoader());
+    xmlSetExternalEntityLoader(Some(entity_loader));
+}
+
+// Hands the request to the loader of the `Parser` running on `ctxt`.
+// Everything else, including the document file itself, goes to the default loader of libxml2.
+unsafe extern "C" fn entity_loader(url: *const c_char, id: *const c_char, ctxt: xmlParserCtxtPtr) -> xmlParserInputPtr {
+    let default = match DEFAULT_ENTITY_LOADER.get() {
+        Some(&Some(default)) => default,
+        _ => return ptr::null_mut(),
+    };
+    if let Some(state) = ParseState::from_ctxt(ctxt) {
+        // Before the parser context has any input, the document itself is being opened
+        let opens_document = ctxt == state.ctxt && (*ctxt).inputNr == 0;
+        if let (Some(loader), false) = (state.entity_loader, opens_document) {
+            return load_entity(state, loader, url, id, ctxt);
+        }
+        if state.policy.is_some() && !opens_document {
+            let url = String::from_utf8_lossy(&xml_bytes(url as *const u8)).into_owned();
+            state.errors.push(XmlError { message: format!("Loading of external resource \"{}\" refused", url), cause: None });
+            return ptr::null_mut();
+        }
+        if !state.catalogs_added {
+            // Resetting the context at the start of the parse drops its catalogs, they are freed together with it
+            state.catalogs_added = true;
+            for catalog in state.catalogs {
+                (*state.ctxt).catalogs = xmlCatalogAddLocal((*state.ctxt).catalogs, catalog.as_ptr() as *const xmlChar);
+            }
+        }
+        if ctxt != state.ctxt && (*ctxt).catalogs.is_null() {
+            // External entities are parsed in contexts of their own, which lack the catalogs of the parser
+            (*ctxt).catalogs = (*state.ctxt).catalogs;
+            let input = default(url, id, ctxt);
+            (*ctxt).catalogs = ptr::null_mut();
+            return input;
+        }
+    }
+    default(url, id, ctxt)
+}
+
+unsafe fn load_entity(state: &mut ParseState, loader: &EntityLoader, url_ptr: *const c_char, id_ptr: *const c_char, ctxt: xmlParserCtxtPtr) -> xmlParserInputPtr {
+    let url = String::from_utf8_lossy(&xml_bytes(url_ptr as *const u8)).into_owned();
+    let id = if id_ptr.is_null() {
+        None
+    } else {
+        Some(CStr::from_ptr(id_ptr).to_string_lossy().into_owned())
+    };
+    let content = match loader(&url, id.as_deref()) {
+        Some(content) => content,
+        None => {
+            state.errors.push(XmlError { message: format!("Loading of external resource \"{}\" refused", url), cause: None });
+            return ptr::null_mut();
+        }
+    };
+    // The buffer copies the content
+    let buffer = xmlParserInputBufferCreateMem(content.as_ptr() as *const c_char, content.len() as c_int, xmlCharEncoding_XML_CHAR_ENCODING_NONE);
+    if buffer.is_null() {
+        return ptr::null_mut();
+    }
+    let input = xmlNewIOInputStream(ctxt, buffer, xmlCharEncoding_XML_CHAR_ENCODING_NONE);
+    if input.is_null() {
+        xmlFreeParserInputBuffer(buffer);
+        return ptr::null_mut();
+    }
+    if !url_ptr.is_null() {
+        // Relative references inside the resource are resolved against its URL
+        (*input).filename = xmlStrdup(url_ptr as *const u8) as *const c_char;
+    }
+    input
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::File;
+    use std::path::Path;
+    use super::*;
+
+    #[test]
+    fn file_input_test() {
+        let parser = Parser::new();
+        // The bytes are passed on as they are, so the declared encoding applies
+        let doc = parser.parse(&File::open("tests/resources/latin1.xml").unwrap()).unwrap();
+        assert_eq!(doc.get_root_element().unwrap().get_content(), "caf\u{e9}");
+
+        // A directory can be opened but not read
+        let errors = match parser.parse(&File::open("tests/resources").unwrap()) {
+            Ok(_) => panic!("reading a directory succeeded"),
+            Err(errors) => errors,
+        };
+        assert!(errors.iter().any(|error| error.message.starts_with("Could not read the input")));
+    }
+
+    #[test]
+    fn entity_loader_test() {
+        let mut parser = Parser::new();
+        parser.set_options(ParseOptions::DEFAULT_XML | ParseOptions::DTDLOAD | ParseOptions::NOENT);
+        parser.set_entity_loader(|url, _| match url {
+            "greeting.dtd" => Some(b"<!ENTITY greeting \"hello\">".to_vec()),
+            _ => None,
+        });
+
+        let doc = parser.parse("<!DOCTYPE root SYSTEM \"greeting.dtd\"><root>&greeting;</root>").unwrap();
+        assert_eq!(doc.get_root_element().unwrap().get_content(), "hello");
+
+        let doc = parser.parse("<!DOCTYPE root [<!ENTITY secret SYSTEM \"file:///etc/passwd\">]><root>&secret;</root>").unwrap();
+        assert_eq!(doc.get_root_element().unwrap().get_content(), "");
+
+        // The document itself is not loaded through the entity loader
+        assert!(parser.parse(Path::new("tests/resources/file01.xml")).is_ok());
+    }
+
+    fn limit(result: Result<Document, Vec<XmlError>>) -> Option<Error> {
+        result.err().and_then(|errors| errors.into_iter().filter_map(|error| error.cause).next())
+    }
+
+    #[test]
+    fn catalog_test() {
+        let mut parser = Parser::new();
+        parser.set_options(ParseOptions::DEFAULT_XML | ParseOptions::DTDLOAD | ParseOptions::NOENT);
+        parser.add_catalog("tests/resources/catalog.xml");
+        let doc = parser.parse("<!DOCTYPE root PUBLIC \"-//Example//DTD Greeting//EN\" \"http://example.com/greeting.dtd\"><root>&greeting;</root>").unwrap();
+        assert_eq!(doc.get_root_element().unwrap().get_content(), "hello");
+    }
+
+    #[test]
+    fn security_policy_test() {
+        let mut parser = Parser::new();
+        parser.set_options(ParseOptions::secure());
+        parser.set_security_policy(SecurityPolicy { max_depth: 3, max_nodes: 5, max_document_bytes: 200, ..SecurityPolicy::default() });
+
+        assert!(parser.parse("<a><b><c/></b><b/></a>").is_ok());
+        assert_eq!(limit(parser.parse("<a><b><c><d/></c></b></a>")), Some(Error::DepthLimit(3)));
+        assert_eq!(limit(parser.parse("<a><b/><b/><b/><b/><b/></a>")), Some(Error::NodeLimit(5)));
+        assert_eq!(limit(parser.parse(&format!("<a>{}</a>", "x".repeat(200)))), Some(Error::DocumentSizeLimit(200)));
+
+        let mut parser = Parser::new();
+        parser.set_security_policy(SecurityPolicy { max_document_bytes: 100, ..SecurityPolicy::default() });
+        let path = Path::new("tests/resources/file01.xml");
+        assert_eq!(limit(parser.parse(path)), Some(Error::DocumentSizeLimit(100)));
+        assert_eq!(limit(parser.parse(&File::open(path).unwrap())), Some(Error::DocumentSizeLimit(100)));
+        assert_eq!(limit(parser.parse(Path::new("/dev/zero"))), Some(Error::DocumentSizeLimit(100)));
+        parser.set_security_policy(SecurityPolicy::default());
+        assert!(parser.parse(path).is_ok());
+    }
+
+    #[test]
+    fn collect_errors_restores_handler_test() {
+        let mut marker = 0u8;
+        let marker_ptr = &mut marker as *mut u8 as *mut c_void;
+        unsafe { xmlSetStructuredErrorFunc(marker_ptr, Some(error_collector)) };
+        let ((), errors) = collect_errors(|| ());
+        assert!(errors.is_empty());
+        unsafe {
+            assert_eq!(*__xmlStructuredErrorContext(), marker_ptr);
+            xmlSetStructuredErrorFunc(ptr::null_mut(), None);
+        }
+    }
+
+    #[test]
+    fn entity_expansion_limit_test() {
+        let mut parser = Parser::new();
+        parser.set_options(ParseOptions::secure() | ParseOptions::NOENT);
+        parser.set_security_policy(SecurityPolicy { max_entity_expansions: 50, ..SecurityPolicy::default() });
+        let lol = "<!DOCTYPE a [<!ENTITY a0 \"lol\"><!ENTITY a1 \"&a0;&a0;&a0;&a0;&a0;&a0;&a0;&a0;\">\
+                   <!ENTITY a2 \"&a1;&a1;&a1;&a1;&a1;&a1;&a1;&a1;\">]><a>&a2;</a>";
+        assert_eq!(limit(parser.parse(lol)), Some(Error::EntityExpansionLimit(50)));
+        let in_attribute = lol.replace("<a>&a2;</a>", "<a b=\"&a2;\"/>");
+        assert_eq!(limit(parser.parse(&in_attribute)), Some(Error::EntityExpansionLimit(50)));
+        let doc = parser.parse("<!DOCTYPE a [<!ENTITY x \"y\">]><a b=\"&x;\">&x;&x;</a>").unwrap();
+        assert_eq!(doc.get_root_element().unwrap().get_content(), "yy");
+
+        let doc = parser.parse("<!DOCTYPE a [<!ENTITY secret SYSTEM \"file:///etc/passwd\">]><a>&secret;</a>");
+        assert_eq!(doc.unwrap().get_root_element().unwrap().get_content(), "");
+    }
+}
@@ -0,0 +1,177 @@
+//! Evaluation of XPath expressions on a `Document`
+
+use std::ffi::CString;
+use std::fmt;
+use std::slice;
+
+use libc;
+
+use libxml2::{xmlXPathContextPtr,
+              xmlXPathObjectPtr,
+              xmlXPathNewContext,
+              xmlXPathFreeContext,
+              xmlXPathRegisterNs,
+              xmlXPathEvalExpression,
+              xmlXPathNodeEval,
+              xmlXPathFreeObject,
+              xmlXPathCastToString,
+              xmlElementType_XML_NAMESPACE_DECL};
+
+use tree::xml_bytes;
+use tree::document::{Document, DocumentRef};
+use tree::node::Node;
+
+/// The context XPath expressions are evaluated in
+pub struct Context {
+    context_ptr: xmlXPathContextPtr,
+    document: DocumentRef,
+}
+
+/// The result of evaluating an XPath expression
+pub struct Object {
+    object_ptr: xmlXPathObjectPtr,
+    document: DocumentRef,
+}
+
+impl Drop for Context {
+    fn drop(&mut self) {
+        unsafe { xmlXPathFreeContext(self.context_ptr) }
+    }
+}
+
+impl Drop for Object {
+    fn drop(&mut self) {
+        unsafe { xmlXPathFreeObject(self.object_ptr) }
+    }
+}
+
+impl Context {
+    /// Creates a new context for evaluating expressions against `document`
+    pub fn new(document: &Document) -> Result<Context, ()> {
+        Context::from_doc_ref(document.doc_ref())
+    }
+
+    pub(crate) fn from_doc_ref(document: DocumentRef) -> Result<Context, ()> {
+        let context_ptr = unsafe { xmlXPathNewContext(document.borrow().doc_ptr) };
+        if context_ptr.is_null() {
+            Err(())
+        } else {
+            Ok(Context { context_ptr, document })
+        }
+    }
+
+    /// Binds `prefix` to the namespace `href` for the expressions evaluated in this context
+    pub fn register_namespace(&self, prefix: &str, href: &str) -> Result<(), ()> {
+        let c_prefix = CString::new(prefix).map_err(|_| ())?;
+        let c_href = CString::new(href).map_err(|_| ())?;
+        let result = unsafe { xmlXPathRegisterNs(self.context_ptr, c_prefix.as_ptr() as *const u8, c_href.as_ptr() as *const u8) };
+        if result == 0 {
+            Ok(())
+        } else {
+            Err(())
+        }
+    }
+
+    /// Evaluates `xpath` with the document as context node
+    pub fn evaluate(&self, xpath: &str) -> Result<Object, ()> {
+        let c_xpath = CString::new(xpath).map_err(|_| ())?;
+        let object_ptr = unsafe { xmlXPathEvalExpression(c_xpath.as_ptr() as *const u8, self.context_ptr) };
+        self.wrap_object(object_ptr)
+    }
+
+    /// Evaluates `xpath` with `node` as context node
+    pub fn node_evaluate(&self, xpath: &str, node: &Node) -> Result<Object, ()> {
+        let c_xpath = CString::new(xpath).map_err(|_| ())?;
+        let object_ptr = unsafe { xmlXPathNodeEval(node.node_ptr(), c_xpath.as_ptr() as *const u8, self.context_ptr) };
+        self.wrap_object(object_ptr)
+    }
+
+    fn wrap_object(&self, object_ptr: xmlXPathObjectPtr) -> Result<Object, ()> {
+        if object_ptr.is_null() {
+            Err(())
+        } else {
+            Ok(Object { object_ptr, document: self.document.clone() })
+        }
+    }
+}
+
+impl Object {
+    /// Returns the number of nodes in the result, 0 if the result is not a node set
+    pub fn get_number_of_nodes(&self) -> usize {
+        unsafe {
+            let node_set = (*self.object_ptr).nodesetval;
+            if node_set.is_null() {
+                0
+            } else {
+                (*node_set).nodeNr as usize
+            }
+        }
+    }
+
+    /// Returns the nodes of the result in document order.
+    /// Namespace nodes are left out, they only live as long as the result.
+    pub fn get_nodes_as_vec(&self) -> Vec<Node> {
+        let mut nodes = Vec::new();
+        unsafe {
+            let node_set = (*self.object_ptr).nodesetval;
+            if node_set.is_null() || (*node_set).nodeTab.is_null() {
+                return nodes;
+            }
+            for &node_ptr in slice::from_raw_parts((*node_set).nodeTab, (*node_set).nodeNr as usize) {
+                if node_ptr.is_null() || (*node_ptr).type_ == xmlElementType_XML_NAMESPACE_DECL {
+                    continue;
+                }
+                nodes.push(Node::wrap(node_ptr, self.document.clone()));
+            }
+        }
+        nodes
+    }
+
+}
+
+impl fmt::Display for Object {
+    /// Formats the result like the XPath `string()` function
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let result = unsafe {
+            let string_ptr = xmlXPathCastToString(self.object_ptr);
+            let result = String::from_utf8_lossy(&xml_bytes(string_ptr)).into_owned();
+            libc::free(string_ptr as *mut libc::c_void);
+            result
+        };
+        f.write_str(&result)
+    }
+}
+
+// Quotes `value` as an XPath string literal, XPath 1.0 has no escapes so mixed quotes need `concat()`
+pub(crate) fn xpath_literal(value: &str) -> String {
+    if !value.contains('\'') {
+        format!("'{}'", value)
+    } else if !value.contains('"') {
+        format!("\"{}\"", value)
+    } else {
+        let parts: Vec<String> = value.split('\'').map(|part| format!("'{}'", part)).collect();
+        format!("concat({})", parts.join(", \"'\", "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn node_evaluate_test() {
+        let doc = Document::parse("<root><child><inner/></child><child/></root>").unwrap();
+        let context = Context::new(&doc).unwrap();
+        let child = doc.get_root_element().unwrap().get_first_child().unwrap();
+        let result = context.node_evaluate("inner", &child).unwrap();
+        assert_eq!(result.get_number_of_nodes(), 1);
+        assert_eq!(result.get_nodes_as_vec()[0].get_name(), "inner");
+        assert!(context.evaluate("//child[").is_err());
+        assert_eq!(context.evaluate("count(//child)").unwrap().to_string(), "2");
+    }
+
+    #[test]
+    fn xpath_literal_test() {
+        assert_eq!(xpath_literal("a'b\"c"), "concat('a', \"'\", 'b\"c')");
+    }
+}
@@ -0,0 +1,13 @@
+//! Tests of the global catalog which need a process of their own,
+//! as nothing may use the catalog before them.
+
+extern crate libxml2;
+
+use libxml2::catalog;
+
+#[test]
+/// Test that an entry can be added before the catalog was used in any other way
+fn add_to_fresh_catalog() {
+  assert!(catalog::add("public", "-//Example//DTD Fresh//EN", "file:///opt/example/fresh.dtd").is_ok());
+  assert_eq!(catalog::resolve(Some("-//Example//DTD Fresh//EN"), None), Some(String::from("file:///opt/example/fresh.dtd")));
+}
@@ -0,0 +1,174 @@
+//! Ownership of nodes moving in and out of the tree.
+//! Every test builds its documents from scratch and drops them at the end of the test,
+//! so running this file under valgrind (or with `MALLOC_CHECK_=3`) reports any leak or double free.
+
+extern crate libxml2;
+
+use libxml2::{xml, Document, Node};
+
+fn create_document() -> (Document, Node) {
+    let mut doc = Document::new().unwrap();
+    let mut root = Node::new("root", None, doc.doc_ref()).unwrap();
+    doc.set_root_element(&mut root);
+    (doc, root)
+}
+
+#[test]
+fn unlink_reattach_drop() {
+    let (doc, mut root) = create_document();
+    let mut child = Node::new("child", None, doc.doc_ref()).unwrap();
+    root.add_child(&mut child).unwrap();
+
+    child.unlink();
+    assert_eq!(root.get_child_nodes().len(), 0);
+    root.add_child(&mut child).unwrap();
+    assert_eq!(root.get_child_nodes().len(), 1);
+
+    drop(child);
+    drop(root);
+    assert_eq!(doc.get_root_element().unwrap().to_string(false), "<root><child/></root>");
+}
+
+#[test]
+fn unlink_twice_then_drop() {
+    let doc = xml("<root><child/></root>").unwrap();
+    let mut child = doc.get_root_element().unwrap().get_first_child().unwrap();
+    child.unlink();
+    child.unlink();
+    assert!(doc.get_root_element().unwrap().get_first_child().is_none());
+}
+
+#[test]
+fn move_between_parents() {
+    let doc = xml("<root><a><child/></a><b/></root>").unwrap();
+    let root = doc.get_root_element().unwrap();
+    let mut a = root.get_first_child().unwrap();
+    let mut b = a.get_next_sibling().unwrap();
+    let mut child = a.get_first_child().unwrap();
+
+    // No explicit unlink, add_child takes the node out of its old parent
+    b.add_child(&mut child).unwrap();
+    assert_eq!(a.get_child_nodes().len(), 0);
+    assert_eq!(b.get_child_nodes().len(), 1);
+
+    a.add_child(&mut child).unwrap();
+    assert_eq!(root.to_string(false), "<root><a><child/></a><b/></root>");
+}
+
+#[test]
+fn unlinked_subtree_is_freed_once() {
+    let doc = xml("<root><a><b><c/></b></a></root>").unwrap();
+    let mut a = doc.get_root_element().unwrap().get_first_child().unwrap();
+    let mut b = a.get_first_child().unwrap();
+    let mut c = b.get_first_child().unwrap();
+    // c stays inside b, which stays inside a: only a is a detached root
+    c.unlink();
+    b.add_child(&mut c).unwrap();
+    b.unlink();
+    a.add_child(&mut b).unwrap();
+    a.unlink();
+    assert_eq!(a.to_string(false), "<a><b><c/></b></a>");
+}
+
+#[test]
+fn new_nodes_are_freed_with_document() {
+    let doc = Document::new().unwrap();
+    let mut parent = Node::new("parent", None, doc.doc_ref()).unwrap();
+    let mut child = Node::new("child", None, doc.doc_ref()).unwrap();
+    parent.add_child(&mut child).unwrap();
+    // Neither node is ever inserted into the document tree
+    let _orphan = Node::new("orphan", None, doc.doc_ref()).unwrap();
+}
+
+#[test]
+fn replaced_root_is_owned_by_document() {
+    let (mut doc, mut old_root) = create_document();
+    let mut new_root = Node::new("new_root", None, doc.doc_ref()).unwrap();
+    doc.set_root_element(&mut new_root);
+    assert_eq!(doc.get_root_element().unwrap().get_name(), "new_root");
+
+    new_root.add_child(&mut old_root).unwrap();
+    assert_eq!(new_root.to_string(false), "<new_root><root/></new_root>");
+}
+
+#[test]
+fn cross_document_add_child_moves_node() {
+    let (mut doc1, mut root1) = create_document();
+    let doc2 = xml("<p:root xmlns:p=\"urn:p\"><p:foreign p:attribute=\"value\"><child/></p:foreign></p:root>").unwrap();
+    let mut foreign = doc2.get_root_element().unwrap().get_first_child().unwrap();
+    let child = foreign.get_first_child().unwrap();
+    root1.add_child(&mut foreign).unwrap();
+    assert!(foreign.same_document(&root1));
+    assert!(child.same_document(&root1));
+    assert_eq!(doc2.get_root_element().unwrap().to_string(false), "<p:root xmlns:p=\"urn:p\"/>");
+    // The namespace is declared again, as the declaration stays behind in doc2
+    drop(doc2);
+    assert_eq!(root1.to_string(false), "<root><p:foreign xmlns:p=\"urn:p\" p:attribute=\"value\"><child/></p:foreign></root>");
+
+    let doc3 = xml("<other/>").unwrap();
+    let mut copy = doc1.import_node(&mut doc3.get_root_element().unwrap()).unwrap();
+    assert!(root1.add_child(&mut copy).is_ok());
+    assert_eq!(doc3.get_root_element().unwrap().get_name(), "other");
+}
+
+#[test]
+fn add_child_rejects_cycles() {
+    let doc = xml("<root><a><b/></a></root>").unwrap();
+    let mut root = doc.get_root_element().unwrap();
+    let mut a = root.get_first_child().unwrap();
+    let mut b = a.get_first_child().unwrap();
+    assert!(b.add_child(&mut a).is_err());
+    assert!(b.add_child(&mut root).is_err());
+    assert!(a.add_child(&mut a.clone()).is_err());
+    assert!(b.add_prev_sibling(a.clone()).is_none());
+    assert_eq!(root.to_string(false), "<root><a><b/></a></root>");
+}
+
+#[test]
+fn merged_text_node_updates_handle() {
+    let doc = xml("<root>hello </root>").unwrap();
+    let mut root = doc.get_root_element().unwrap();
+    let mut text = root.get_first_child().unwrap();
+    let alias = text.clone();
+    text.unlink();
+    root.append_text("world");
+    // Re-adding the text merges it into the existing text node
+    root.add_child(&mut text).unwrap();
+    assert_eq!(text, root.get_first_child().unwrap());
+    assert_eq!(root.get_content(), "worldhello ");
+    // The merged node has been freed, the other handle to it is invalidated
+    assert!(text.is_valid());
+    assert!(!alias.is_valid());
+}
+
+#[test]
+fn replaced_attribute_node_stays_valid() {
+    let doc = xml("<root attribute=\"old\"><child attribute=\"new\"/></root>").unwrap();
+    let mut root = doc.get_root_element().unwrap();
+    let old = root.get_property_node("attribute").unwrap();
+    let mut new = root.get_first_child().unwrap().get_property_node("attribute").unwrap();
+    root.add_child(&mut new).unwrap();
+    assert_eq!(root.get_property("attribute"), Some(String::from("new")));
+    assert_eq!(old.get_content(), "old");
+    assert_eq!(old.get_parent(), None);
+}
+
+#[test]
+fn replaced_content_keeps_child_handles() {
+    let doc = xml("<root><child>text</child>tail</root>").unwrap();
+    let mut root = doc.get_root_element().unwrap();
+    let child = root.get_first_child().unwrap();
+    root.set_content("new");
+    assert_eq!(root.to_string(false), "<root>new</root>");
+    assert_eq!(child.to_string(false), "<child>text</child>");
+}
+
+#[test]
+fn removed_attribute_node_stays_valid() {
+    let doc = xml("<root attribute=\"value\"/>").unwrap();
+    let mut root = doc.get_root_element().unwrap();
+    let attr = root.get_property_node("attribute").unwrap();
+    root.remove_property("attribute");
+    assert_eq!(root.get_property("attribute"), None);
+    assert_eq!(attr.get_content(), "value");
+}
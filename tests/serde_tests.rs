@@ -0,0 +1,117 @@
+//! Mapping documents to Rust types with serde, run with `--features serde`.
+#![cfg(feature = "serde")]
+
+extern crate libxml2;
+#[macro_use]
+extern crate serde_derive;
+
+use libxml2::{xml, from_document, from_node, to_document};
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename = "config")]
+struct Config {
+    #[serde(rename = "@version")]
+    version: u32,
+    #[serde(rename = "@debug", default)]
+    debug: bool,
+    name: String,
+    #[serde(rename = "server")]
+    servers: Vec<Server>,
+    mode: Mode,
+    limits: Option<Limits>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct Server {
+    #[serde(rename = "@port")]
+    port: u16,
+    #[serde(rename = "$value")]
+    host: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+enum Mode {
+    Fast,
+    Safe,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct Limits {
+    #[serde(rename = "@tags")]
+    tags: Vec<String>,
+}
+
+#[test]
+fn deserialize_struct() {
+    let doc = xml(r#"<config version="2" debug="1">
+        <name>example</name>
+        <server port="80">one.example.com</server>
+        <mode> safe </mode>
+        <server port="8080">two.example.com</server>
+    </config>"#).unwrap();
+    let config: Config = from_document(&doc).unwrap();
+    assert_eq!(config, Config {
+        version: 2,
+        debug: true,
+        name: String::from("example"),
+        servers: vec![
+            Server { port: 80, host: String::from("one.example.com") },
+            Server { port: 8080, host: String::from("two.example.com") },
+        ],
+        mode: Mode::Safe,
+        limits: None,
+    });
+}
+
+#[test]
+fn deserialize_single_element_as_sequence() {
+    let doc = xml(r#"<config version="1"><name>n</name><server port="1">h</server><mode>fast</mode><limits tags="a b"/></config>"#).unwrap();
+    let config: Config = from_node(&doc.get_root_element().unwrap()).unwrap();
+    assert_eq!(config.servers.len(), 1);
+    assert_eq!(config.mode, Mode::Fast);
+    assert_eq!(config.limits.unwrap().tags, vec!["a", "b"]);
+}
+
+#[test]
+fn deserialize_errors() {
+    let doc = xml(r#"<config version="x"><name>n</name><mode>fast</mode></config>"#).unwrap();
+    assert!(from_document::<Config>(&doc).is_err());
+
+    let doc = xml(r#"<config version="1"><name>a</name><name>b</name><mode>fast</mode></config>"#).unwrap();
+    assert!(from_document::<Config>(&doc).is_err());
+}
+
+#[test]
+fn serialize_struct() {
+    let config = Config {
+        version: 3,
+        debug: false,
+        name: String::from("a < b"),
+        servers: vec![
+            Server { port: 80, host: String::from("one") },
+            Server { port: 81, host: String::from("two") },
+        ],
+        mode: Mode::Fast,
+        limits: None,
+    };
+    let doc = to_document(&config).unwrap();
+    assert_eq!(doc.get_root_element().unwrap().to_string(false),
+               "<config version=\"3\" debug=\"false\"><name>a &lt; b</name>\
+                <server port=\"80\">one</server><server port=\"81\">two</server><mode>fast</mode></config>");
+}
+
+#[test]
+fn serialize_round_trip() {
+    let config = Config {
+        version: 1,
+        debug: true,
+        name: String::from("round trip"),
+        servers: vec![Server { port: 443, host: String::from("example.com") }],
+        mode: Mode::Safe,
+        limits: Some(Limits { tags: vec![String::from("x"), String::from("y")] }),
+    };
+    let doc = to_document(&config).unwrap();
+    let parsed = xml(&doc.to_string(false)).unwrap();
+    assert_eq!(from_document::<Config>(&parsed).unwrap(), config);
+}
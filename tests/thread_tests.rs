@@ -0,0 +1,60 @@
+//! Parsing and sharing documents across threads.
+
+extern crate libxml2;
+
+use std::thread;
+
+use libxml2::{xml, xml_with_options, Document, Node, ParseOptions};
+
+#[test]
+/// Errors of concurrent parses end up with the parse that caused them
+fn concurrent_parsing_keeps_errors_apart() {
+    let handles: Vec<_> = (0..8).map(|i| {
+        thread::spawn(move || {
+            for _ in 0..50 {
+                if i % 2 == 0 {
+                    let doc = xml("<root><child>text</child></root>").unwrap();
+                    assert_eq!(doc.get_root_element().unwrap().get_name(), "root");
+                } else {
+                    let result = xml_with_options("<root><child></root>", "", "utf-8", ParseOptions::STRICT);
+                    assert!(!result.err().unwrap().is_empty());
+                }
+            }
+        })
+    }).collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+}
+
+#[test]
+fn sync_document_is_shared_between_threads() {
+    let doc = xml("<root></root>").unwrap().into_sync().ok().unwrap();
+
+    let handles: Vec<_> = (0..4).map(|_| {
+        let doc = doc.clone();
+        thread::spawn(move || {
+            doc.with(|doc| {
+                let mut child = Node::new("child", None, doc.doc_ref()).unwrap();
+                doc.get_root_element().unwrap().add_child(&mut child).unwrap();
+            });
+        })
+    }).collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    let count = doc.with(|doc| doc.get_root_element().unwrap().get_child_elements().len());
+    assert_eq!(count, 4);
+    let doc = doc.into_document().ok().unwrap();
+    assert_eq!(doc.get_root_element().unwrap().to_string(false), "<root><child/><child/><child/><child/></root>");
+}
+
+#[test]
+fn into_sync_requires_unique_handle() {
+    let doc = Document::new().unwrap();
+    let node = Node::new("node", None, doc.doc_ref()).unwrap();
+    let doc = doc.into_sync().err().unwrap();
+    drop(node);
+    assert!(doc.into_sync().is_ok());
+}